@@ -81,6 +81,7 @@ fn init_datasets() -> Result<PathBuf, Box<dyn std::error::Error>> {
                             "{{prefix}}{{msg:.cyan/blue}} [{{bar}}] {{pos}}/{}",
                             progress.total_objects()
                         ))
+                        .unwrap()
                         .progress_chars("#> "),
                 )
                 .with_prefix("    ")
@@ -167,7 +168,7 @@ fn parse_datasets<P: AsRef<Path>>(path: P) -> Result<Vec<Dataset>, Box<dyn std::
             let pb = ProgressBar::new(total_size)
                 .with_style(
                     ProgressStyle::default_bar()
-                        .template("    {prefix:.green} [{bar}] {msg}")
+                        .template("    {prefix:.green} [{bar}] {msg}")?
                         .progress_chars("#> "),
                 )
                 .with_prefix("Parsing")
@@ -531,6 +531,33 @@ fn successive_or(c: &mut Criterion) {
                 BatchSize::LargeInput,
             );
         });
+
+        group.bench_function(BenchmarkId::new("Multi Or Len", &dataset.name), |b| {
+            b.iter(|| black_box(dataset.bitmaps.iter().union_len()));
+        });
+
+        let treemaps: Vec<_> = dataset
+            .bitmaps
+            .iter()
+            .map(|bitmap| RoaringTreemap::from_bitmaps([(0, bitmap.clone())]))
+            .collect();
+
+        group.bench_function(
+            BenchmarkId::new("Treemap Successive Or Assign Ref", &dataset.name),
+            |b| {
+                b.iter(|| {
+                    let mut output = RoaringTreemap::new();
+                    for treemap in &treemaps {
+                        output |= treemap;
+                    }
+                    black_box(output)
+                });
+            },
+        );
+
+        group.bench_function(BenchmarkId::new("Treemap Multi Or Ref", &dataset.name), |b| {
+            b.iter(|| black_box(treemaps.iter().union()));
+        });
     }
 
     group.finish();
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod test {
     use crate::bitmap::container::Container;
-    use crate::bitmap::store::{ArrayStore, BitmapStore, Store};
+    use crate::bitmap::store::{ArrayStore, BitmapStore, RunStore, Store};
     use crate::RoaringBitmap;
     use proptest::bits::{BitSetLike, BitSetStrategy, SampledBitSetStrategy};
     use proptest::collection::{vec, SizeRange};
@@ -150,11 +150,42 @@ mod test {
         }
     }
 
+    impl Debug for RunStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            if self.run_amount() < 8 {
+                let runs: Vec<_> = self.as_slice().iter().map(|iv| iv.start..=iv.end()).collect();
+                write!(f, "RunStore<{:?}>", runs)
+            } else {
+                write!(
+                    f,
+                    "RunStore<{:?} values in {:?} runs between {:?} and {:?}>",
+                    self.len(),
+                    self.run_amount(),
+                    self.min().unwrap(),
+                    self.max().unwrap()
+                )
+            }
+        }
+    }
+
+    impl RunStore {
+        pub fn arbitrary() -> impl Strategy<Value = RunStore> {
+            vec((any::<u16>(), ..=4096_u16), 1..=32).prop_map(|runs| {
+                let mut store = RunStore::new();
+                for (start, len) in runs {
+                    store.insert_range(start..=start.saturating_add(len));
+                }
+                store
+            })
+        }
+    }
+
     impl Debug for Store {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             match self {
                 Store::Array(a) => write!(f, "Store({:?})", a),
                 Store::Bitmap(b) => write!(f, "Store({:?})", b),
+                Store::Run(r) => write!(f, "Store({:?})", r),
             }
         }
     }
@@ -165,6 +196,7 @@ mod test {
                 ArrayStore::sampled(1..=4096, ..=u16::MAX as usize).prop_map(Store::Array),
                 BitmapStore::sampled(4097..u16::MAX as usize, ..=u16::MAX as usize)
                     .prop_map(Store::Bitmap),
+                RunStore::arbitrary().prop_map(Store::Run),
             ]
         }
     }
@@ -182,6 +214,7 @@ mod test {
        }
     }
 
+    #[allow(missing_docs)]
     impl RoaringBitmap {
         prop_compose! {
             pub fn arbitrary()(bitmap in (0usize..=16).prop_flat_map(containers)) -> RoaringBitmap {
@@ -19,6 +19,11 @@ pub struct Iter<'a> {
     inner: store::Iter<'a>,
 }
 
+pub struct Ranges<'a> {
+    key: u16,
+    inner: store::Ranges<'a>,
+}
+
 impl Container {
     pub fn new(key: u16) -> Container {
         Container { key, store: Store::new() }
@@ -44,10 +49,10 @@ impl Container {
     }
 
     pub fn insert_range(&mut self, range: RangeInclusive<u16>) -> u64 {
-        // If inserting the range will make this a bitmap by itself, do it now
+        // If inserting the range will make this a run by itself, do it now
         if range.len() as u64 > ARRAY_LIMIT {
             if let Store::Array(arr) = &self.store {
-                self.store = Store::Bitmap(arr.to_bitmap_store());
+                self.store = Store::Run(arr.to_run_store());
             }
         }
         let inserted = self.store.insert_range(range);
@@ -94,6 +99,16 @@ impl Container {
         result
     }
 
+    pub fn flip_range(&mut self, range: RangeInclusive<u16>) {
+        self.store.flip_range(range);
+        self.ensure_correct_store();
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(u16) -> bool) {
+        self.store.retain(f);
+        self.ensure_correct_store();
+    }
+
     pub fn contains(&self, index: u16) -> bool {
         self.store.contains(index)
     }
@@ -126,10 +141,75 @@ impl Container {
         self.store.max()
     }
 
+    pub fn next_value(&self, index: u16) -> Option<u16> {
+        self.store.next_value(index)
+    }
+
+    pub fn previous_value(&self, index: u16) -> Option<u16> {
+        self.store.previous_value(index)
+    }
+
+    pub fn next_absent(&self, index: u16) -> Option<u16> {
+        self.store.next_absent(index)
+    }
+
+    pub fn previous_absent(&self, index: u16) -> Option<u16> {
+        self.store.previous_absent(index)
+    }
+
     pub fn rank(&self, index: u16) -> u64 {
         self.store.rank(index)
     }
 
+    pub fn ranges(&self) -> Ranges<'_> {
+        Ranges { key: self.key, inner: self.store.ranges() }
+    }
+
+    /// Converts the store to a run store when it is the smallest representation,
+    /// or away from a run store when it is not.
+    ///
+    /// Returns whether the container ends up as a run container.
+    pub(crate) fn optimize(&mut self) -> bool {
+        match &self.store {
+            Store::Array(ref vec) => {
+                let size_as_run = 2 + 4 * vec.run_amount();
+                if size_as_run < 2 * vec.len() {
+                    self.store = Store::Run(vec.to_run_store());
+                    return true;
+                }
+            }
+            Store::Bitmap(ref bits) => {
+                let size_as_run = 2 + 4 * bits.run_amount();
+                if size_as_run < 8 * 1024 {
+                    self.store = Store::Run(bits.to_run_store());
+                    return true;
+                }
+            }
+            Store::Run(..) => {
+                self.ensure_correct_store();
+                return matches!(self.store, Store::Run(..));
+            }
+        }
+        false
+    }
+
+    /// Converts a run store back to either an array or a bitmap store.
+    ///
+    /// Returns whether the container was a run container.
+    pub(crate) fn remove_run_compression(&mut self) -> bool {
+        match &self.store {
+            Store::Run(ref runs) => {
+                if runs.len() <= ARRAY_LIMIT {
+                    self.store = Store::Array(runs.to_array_store());
+                } else {
+                    self.store = Store::Bitmap(runs.to_bitmap_store());
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub(crate) fn ensure_correct_store(&mut self) {
         match &self.store {
             Store::Bitmap(ref bits) => {
@@ -142,6 +222,19 @@ impl Container {
                     self.store = Store::Bitmap(vec.to_bitmap_store())
                 }
             }
+            Store::Run(ref runs) => {
+                // Only keep the run container while it is the smallest representation
+                let len = runs.len();
+                let size_as_run = 2 + 4 * runs.run_amount();
+                let size_as_other = if len <= ARRAY_LIMIT { 2 * len } else { 8 * 1024 };
+                if size_as_run > size_as_other {
+                    if len <= ARRAY_LIMIT {
+                        self.store = Store::Array(runs.to_array_store())
+                    } else {
+                        self.store = Store::Bitmap(runs.to_bitmap_store())
+                    }
+                }
+            }
         };
     }
 }
@@ -265,12 +358,49 @@ impl<'a> Iterator for Iter<'a> {
     }
 }
 
+impl Iterator for Ranges<'_> {
+    type Item = RangeInclusive<u32>;
+    fn next(&mut self) -> Option<RangeInclusive<u32>> {
+        self.inner.next().map(|r| util::join(self.key, *r.start())..=util::join(self.key, *r.end()))
+    }
+}
+
+impl DoubleEndedIterator for Ranges<'_> {
+    fn next_back(&mut self) -> Option<RangeInclusive<u32>> {
+        self.inner
+            .next_back()
+            .map(|r| util::join(self.key, *r.start())..=util::join(self.key, *r.end()))
+    }
+}
+
 impl DoubleEndedIterator for Iter<'_> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.inner.next_back().map(|i| util::join(self.key, i))
     }
 }
 
+impl Iter<'_> {
+    /// Writes the next values into `buf`, returns the number of written values.
+    pub fn next_many(&mut self, buf: &mut [u32]) -> usize {
+        self.inner.next_many(util::join(self.key, 0), buf)
+    }
+
+    /// Skips the values lower than `index`, returns the number of skipped values.
+    pub fn advance_to(&mut self, index: u16) -> u64 {
+        self.inner.advance_to(index)
+    }
+
+    /// Skips the values greater than `index` from the back, returns the number of skipped values.
+    pub fn advance_back_to(&mut self, index: u16) -> u64 {
+        self.inner.advance_back_to(index)
+    }
+
+    /// Skips all the remaining values, returns the number of skipped values.
+    pub fn skip_all(&mut self) -> u64 {
+        self.inner.advance_to(u16::MAX) + self.inner.next().map_or(0, |_| 1)
+    }
+}
+
 impl fmt::Debug for Container {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         format!("Container<{:?} @ {:?}>", self.len(), self.key).fmt(formatter)
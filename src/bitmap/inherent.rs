@@ -1,9 +1,13 @@
 use std::cmp::Ordering;
-use std::ops::RangeBounds;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+use retain_mut::RetainMut;
 
 use crate::RoaringBitmap;
 
 use super::container::Container;
+use super::store::Store;
 use super::util;
 
 impl RoaringBitmap {
@@ -203,15 +207,11 @@ impl RoaringBitmap {
     pub fn remove(&mut self, value: u32) -> bool {
         let (key, index) = util::split(value);
         match self.containers.binary_search_by_key(&key, |c| c.key) {
-            Ok(loc) => {
-                if self.containers[loc].remove(index) {
-                    if self.containers[loc].len() == 0 {
-                        self.containers.remove(loc);
-                    }
-                    true
-                } else {
-                    false
+            Ok(loc) if self.containers[loc].remove(index) => {
+                if self.containers[loc].len() == 0 {
+                    self.containers.remove(loc);
                 }
+                true
             }
             _ => false,
         }
@@ -260,6 +260,455 @@ impl RoaringBitmap {
         removed
     }
 
+    /// Removes the smallest value of the set and returns it, or `None` if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (1..4).collect();
+    /// assert_eq!(rb.pop_min(), Some(1));
+    /// assert_eq!(rb.pop_min(), Some(2));
+    /// assert!(rb.iter().eq([3]));
+    /// ```
+    pub fn pop_min(&mut self) -> Option<u32> {
+        let container = self.containers.first_mut()?;
+        let index = container.min()?;
+        let value = util::join(container.key, index);
+        container.remove(index);
+        if container.len() == 0 {
+            self.containers.remove(0);
+        }
+        Some(value)
+    }
+
+    /// Removes the greatest value of the set and returns it, or `None` if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (1..4).collect();
+    /// assert_eq!(rb.pop_max(), Some(3));
+    /// assert_eq!(rb.pop_max(), Some(2));
+    /// assert!(rb.iter().eq([1]));
+    /// ```
+    pub fn pop_max(&mut self) -> Option<u32> {
+        let container = self.containers.last_mut()?;
+        let index = container.max()?;
+        let value = util::join(container.key, index);
+        container.remove(index);
+        if container.len() == 0 {
+            self.containers.pop();
+        }
+        Some(value)
+    }
+
+    /// Removes the `n` smallest values of the set.
+    /// Returns the number of removed values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (1..10).collect();
+    /// assert_eq!(rb.remove_smallest(3), 3);
+    /// assert!(rb.iter().eq(4..10));
+    /// assert_eq!(rb.remove_smallest(10), 6);
+    /// assert!(rb.is_empty());
+    /// ```
+    pub fn remove_smallest(&mut self, n: u64) -> u64 {
+        let mut removed = 0;
+        let count = self
+            .containers
+            .iter()
+            .take_while(|container| {
+                let fits = removed + container.len() <= n;
+                if fits {
+                    removed += container.len();
+                }
+                fits
+            })
+            .count();
+        self.containers.drain(..count);
+
+        // The remaining values are less than the length of the first container
+        if let Some(container) = self.containers.first_mut().filter(|_| removed < n) {
+            let last = container.store.select((n - removed - 1) as u16).unwrap();
+            removed += container.remove_range(0..=last);
+        }
+        removed
+    }
+
+    /// Removes the `n` greatest values of the set.
+    /// Returns the number of removed values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (1..10).collect();
+    /// assert_eq!(rb.remove_biggest(3), 3);
+    /// assert!(rb.iter().eq(1..7));
+    /// assert_eq!(rb.remove_biggest(10), 6);
+    /// assert!(rb.is_empty());
+    /// ```
+    pub fn remove_biggest(&mut self, n: u64) -> u64 {
+        let mut removed = 0;
+        let count = self
+            .containers
+            .iter()
+            .rev()
+            .take_while(|container| {
+                let fits = removed + container.len() <= n;
+                if fits {
+                    removed += container.len();
+                }
+                fits
+            })
+            .count();
+        self.containers.truncate(self.containers.len() - count);
+
+        // The remaining values are less than the length of the last container
+        if let Some(container) = self.containers.last_mut().filter(|_| removed < n) {
+            let first = container.store.select((container.len() - (n - removed)) as u16).unwrap();
+            removed += container.remove_range(first..=u16::MAX);
+        }
+        removed
+    }
+
+    /// Flips the values in the range: the values of the range that are in the set are
+    /// removed and the others are inserted. Only the containers of the range are modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..10).collect();
+    /// rb.flip_range(5..15);
+    /// assert!(rb.iter().eq((0..5).chain(10..15)));
+    /// ```
+    pub fn flip_range<R>(&mut self, range: R)
+    where
+        R: RangeBounds<u32>,
+    {
+        let (start, end) = match util::convert_range_to_inclusive(range) {
+            Some(range) => (*range.start(), *range.end()),
+            None => return,
+        };
+
+        let (start_container_key, start_index) = util::split(start);
+        let (end_container_key, end_index) = util::split(end);
+
+        let first = self.containers.partition_point(|c| c.key < start_container_key);
+        let last = self.containers.partition_point(|c| c.key <= end_container_key);
+        let mut existing = self.containers.drain(first..last).peekable();
+
+        // The keys of the range that have no container get a new one holding the flipped range
+        let mut flipped = Vec::new();
+        for key in start_container_key..=end_container_key {
+            let a = if key == start_container_key { start_index } else { 0 };
+            let b = if key == end_container_key { end_index } else { u16::MAX };
+            match existing.next_if(|c| c.key == key) {
+                Some(mut container) => {
+                    container.flip_range(a..=b);
+                    if container.len() > 0 {
+                        flipped.push(container);
+                    }
+                }
+                None => {
+                    let mut container = Container::new(key);
+                    container.insert_range(a..=b);
+                    flipped.push(container);
+                }
+            }
+        }
+
+        drop(existing);
+        self.containers.splice(first..first, flipped);
+    }
+
+    /// Returns a copy of the set with the values in the range flipped,
+    /// see [`RoaringBitmap::flip_range`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let rb: RoaringBitmap = (0..10).collect();
+    /// assert!(rb.flipped(5..15).iter().eq((0..5).chain(10..15)));
+    /// ```
+    pub fn flipped<R>(&self, range: R) -> RoaringBitmap
+    where
+        R: RangeBounds<u32>,
+    {
+        let mut flipped = self.clone();
+        flipped.flip_range(range);
+        flipped
+    }
+
+    /// Unions in-place with the complement of `other` in the universe `0..universe_end`:
+    /// the values lower than `universe_end` that are not in `other` are inserted in the set.
+    /// The values of the set that are outside of the universe are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = [1, 100].into_iter().collect();
+    /// let other: RoaringBitmap = (2..8).collect();
+    ///
+    /// rb.or_not(&other, 10);
+    /// assert!(rb.iter().eq([0, 1, 8, 9, 100]));
+    /// ```
+    pub fn or_not(&mut self, other: &RoaringBitmap, universe_end: u64) {
+        let universe_end = universe_end.min(1 << 32);
+        if universe_end == 0 {
+            return;
+        }
+
+        let max = (universe_end - 1) as u32;
+        let (end_container_key, _) = util::split(max);
+        let last = other.containers.partition_point(|c| c.key <= end_container_key);
+        let mut complement = RoaringBitmap { containers: other.containers[..last].to_vec() };
+        complement.remove_range((Bound::Excluded(max), Bound::Unbounded));
+        complement.flip_range(..=max);
+        *self |= complement;
+    }
+
+    /// Returns a copy of the set with `offset` added to every value,
+    /// the values that overflow or underflow are dropped.
+    ///
+    /// When the offset is a multiple of 65,536 only the keys of the containers are changed,
+    /// otherwise the values of each container are split between two neighbouring containers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let rb: RoaringBitmap = [0, 10, 70_000, u32::MAX].into_iter().collect();
+    ///
+    /// assert!(rb.add_offset(100).iter().eq([100, 110, 70_100]));
+    /// assert!(rb.add_offset(-10).iter().eq([0, 69_990, u32::MAX - 10]));
+    /// ```
+    pub fn add_offset(&self, offset: i64) -> RoaringBitmap {
+        let key_offset = offset.div_euclid(1 << 16);
+        let offset = offset.rem_euclid(1 << 16) as u16;
+
+        let mut containers: Vec<Container> = Vec::with_capacity(self.containers.len() + 1);
+        for container in &self.containers {
+            let key = i64::from(container.key) + key_offset;
+            let (low, high) = if offset == 0 {
+                (container.store.clone(), Store::new())
+            } else {
+                container.store.add_offset(offset)
+            };
+            for (key, store) in [(key, low), (key + 1, high)] {
+                if key < 0 || key > i64::from(u16::MAX) || store.len() == 0 {
+                    continue;
+                }
+                let mut shifted = Container { key: key as u16, store };
+                shifted.ensure_correct_store();
+                // The overflow of a container goes to the same key as the next container
+                match containers.last_mut() {
+                    Some(last) if last.key == shifted.key => *last |= shifted,
+                    _ => containers.push(shifted),
+                }
+            }
+        }
+        RoaringBitmap { containers }
+    }
+
+    /// Retains only the values specified by the predicate, the predicate is called
+    /// once for each value, in ascending order. The emptied containers are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..10).collect();
+    /// rb.retain(|value| value % 2 == 0);
+    /// assert!(rb.iter().eq([0, 2, 4, 6, 8]));
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32) -> bool,
+    {
+        RetainMut::retain_mut(&mut self.containers, |container| {
+            let key = container.key;
+            container.retain(|index| f(util::join(key, index)));
+            container.len() != 0
+        })
+    }
+
+    /// Removes the values specified by the predicate and returns them as a new bitmap,
+    /// the predicate is called once for each value, in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..10).collect();
+    /// let odd = rb.extract_if(|value| value % 2 == 1);
+    /// assert!(rb.iter().eq([0, 2, 4, 6, 8]));
+    /// assert!(odd.iter().eq([1, 3, 5, 7, 9]));
+    /// ```
+    pub fn extract_if<F>(&mut self, mut f: F) -> RoaringBitmap
+    where
+        F: FnMut(u32) -> bool,
+    {
+        let mut extracted = RoaringBitmap::new();
+        RetainMut::retain_mut(&mut self.containers, |container| {
+            let key = container.key;
+            let mut removed = Container::new(key);
+            container.retain(|index| {
+                if f(util::join(key, index)) {
+                    removed.push_unchecked(index);
+                    false
+                } else {
+                    true
+                }
+            });
+            if removed.len() != 0 {
+                extracted.containers.push(removed);
+            }
+            container.len() != 0
+        });
+        extracted
+    }
+
+    /// Removes the values in the range and returns them as a new bitmap.
+    /// The containers that are entirely in the range are moved without being copied.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..10).collect();
+    /// let drained = rb.drain_range(2..5);
+    /// assert!(rb.iter().eq([0, 1, 5, 6, 7, 8, 9]));
+    /// assert!(drained.iter().eq([2, 3, 4]));
+    /// ```
+    pub fn drain_range<R>(&mut self, range: R) -> RoaringBitmap
+    where
+        R: RangeBounds<u32>,
+    {
+        let (start, end) = match util::convert_range_to_inclusive(range) {
+            Some(range) => (*range.start(), *range.end()),
+            None => return RoaringBitmap::new(),
+        };
+
+        let (start_container_key, start_index) = util::split(start);
+        let (end_container_key, end_index) = util::split(end);
+
+        let first = self.containers.partition_point(|c| c.key < start_container_key);
+        let last = self.containers.partition_point(|c| c.key <= end_container_key);
+        let mut drained: Vec<Container> = self.containers.drain(first..last).collect();
+
+        // The values of the boundary containers that are outside of the range are kept
+        let mut kept = Vec::new();
+        for container in &mut drained {
+            let a = if container.key == start_container_key { start_index } else { 0 };
+            let b = if container.key == end_container_key { end_index } else { u16::MAX };
+            if a > 0 || b < u16::MAX {
+                let mut outside = container.clone();
+                outside.remove_range(a..=b);
+                *container -= &outside;
+                if outside.len() != 0 {
+                    kept.push(outside);
+                }
+            }
+        }
+
+        drained.retain(|container| container.len() != 0);
+        self.containers.splice(first..first, kept);
+        RoaringBitmap { containers: drained }
+    }
+
+    /// Splits the set in two at the given value.
+    /// Returns a new bitmap with all the values greater than or equal to `at`,
+    /// only the container holding `at` is split, the others are moved.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..10).collect();
+    /// let high = rb.split_off(4);
+    /// assert!(rb.iter().eq(0..4));
+    /// assert!(high.iter().eq(4..10));
+    /// ```
+    pub fn split_off(&mut self, at: u32) -> RoaringBitmap {
+        let (key, index) = util::split(at);
+        let position = self.containers.partition_point(|c| c.key < key);
+        let mut containers = self.containers.split_off(position);
+
+        if let Some(first) = containers.first_mut().filter(|c| c.key == key && index > 0) {
+            let mut low = first.clone();
+            low.remove_range(index..=u16::MAX);
+            first.remove_range(0..=index - 1);
+            if low.len() != 0 {
+                self.containers.push(low);
+            }
+            if first.len() == 0 {
+                containers.remove(0);
+            }
+        }
+
+        RoaringBitmap { containers }
+    }
+
+    /// Moves all the values of `other` at the end of this set.
+    ///
+    /// All the values of `other` must be strictly greater than the greatest value
+    /// of the set, the containers are then moved without being copied.
+    /// Otherwise nothing is appended and `other` is returned in the error.
+    ///
+    /// Returns `Ok` with the number of elements appended to the set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..10).collect();
+    /// assert_eq!(rb.append_bitmap((10..20).collect()), Ok(10));
+    /// assert!(rb.iter().eq(0..20));
+    ///
+    /// let other: RoaringBitmap = (5..30).collect();
+    /// assert_eq!(rb.append_bitmap(other.clone()), Err(other));
+    /// ```
+    pub fn append_bitmap(&mut self, other: RoaringBitmap) -> Result<u64, RoaringBitmap> {
+        match (self.max(), other.min()) {
+            (_, None) => return Ok(0),
+            (Some(max), Some(min)) if min <= max => return Err(other),
+            _ => (),
+        }
+
+        let appended = other.len();
+        let mut containers = other.containers.into_iter();
+        if let Some(last) = self.containers.last_mut() {
+            if let Some(first) = containers.as_slice().first().filter(|c| c.key == last.key) {
+                *last |= first;
+                containers.next();
+            }
+        }
+        self.containers.extend(containers);
+        Ok(appended)
+    }
+
     /// Returns `true` if this set contains the specified integer.
     ///
     /// # Examples
@@ -513,6 +962,124 @@ impl RoaringBitmap {
         self.containers.last().and_then(|tail| tail.max().map(|max| util::join(tail.key, max)))
     }
 
+    /// Returns the smallest value in the set that is greater than or equal to `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let rb: RoaringBitmap = [3, 100_000].into_iter().collect();
+    ///
+    /// assert_eq!(rb.next_value(3), Some(3));
+    /// assert_eq!(rb.next_value(4), Some(100_000));
+    /// assert_eq!(rb.next_value(100_001), None);
+    /// ```
+    pub fn next_value(&self, value: u32) -> Option<u32> {
+        let (key, index) = util::split(value);
+        let mut i = self.containers.partition_point(|c| c.key < key);
+        if let Some(container) = self.containers.get(i).filter(|c| c.key == key) {
+            if let Some(next) = container.next_value(index) {
+                return Some(util::join(key, next));
+            }
+            i += 1;
+        }
+        let container = self.containers.get(i)?;
+        container.min().map(|min| util::join(container.key, min))
+    }
+
+    /// Returns the greatest value in the set that is lower than or equal to `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let rb: RoaringBitmap = [3, 100_000].into_iter().collect();
+    ///
+    /// assert_eq!(rb.previous_value(100_000), Some(100_000));
+    /// assert_eq!(rb.previous_value(99_999), Some(3));
+    /// assert_eq!(rb.previous_value(2), None);
+    /// ```
+    pub fn previous_value(&self, value: u32) -> Option<u32> {
+        let (key, index) = util::split(value);
+        let mut i = self.containers.partition_point(|c| c.key <= key);
+        if let Some(container) = i.checked_sub(1).map(|i| &self.containers[i]) {
+            if container.key == key {
+                if let Some(previous) = container.previous_value(index) {
+                    return Some(util::join(key, previous));
+                }
+                i -= 1;
+            }
+        }
+        let container = &self.containers[i.checked_sub(1)?];
+        container.max().map(|max| util::join(container.key, max))
+    }
+
+    /// Returns the smallest value that is greater than or equal to `value`
+    /// and that is not in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..100_000).collect();
+    ///
+    /// assert_eq!(rb.next_absent(5), Some(100_000));
+    /// assert_eq!(rb.next_absent(100_001), Some(100_001));
+    /// rb.insert_range(100_000..);
+    /// assert_eq!(rb.next_absent(5), None);
+    /// ```
+    pub fn next_absent(&self, value: u32) -> Option<u32> {
+        let (mut key, mut index) = util::split(value);
+        let i = self.containers.partition_point(|c| c.key < key);
+        // Skip the containers that are full from `index` to the end
+        for container in &self.containers[i..] {
+            if container.key != key {
+                break;
+            }
+            if let Some(next) = container.next_absent(index) {
+                return Some(util::join(key, next));
+            }
+            key = key.checked_add(1)?;
+            index = 0;
+        }
+        Some(util::join(key, index))
+    }
+
+    /// Returns the greatest value that is lower than or equal to `value`
+    /// and that is not in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (100_000..200_000).collect();
+    ///
+    /// assert_eq!(rb.previous_absent(150_000), Some(99_999));
+    /// assert_eq!(rb.previous_absent(99_999), Some(99_999));
+    /// rb.insert_range(..100_000);
+    /// assert_eq!(rb.previous_absent(150_000), None);
+    /// ```
+    pub fn previous_absent(&self, value: u32) -> Option<u32> {
+        let (mut key, mut index) = util::split(value);
+        let i = self.containers.partition_point(|c| c.key <= key);
+        // Skip the containers that are full from the start to `index`
+        for container in self.containers[..i].iter().rev() {
+            if container.key != key {
+                break;
+            }
+            if let Some(previous) = container.previous_absent(index) {
+                return Some(util::join(key, previous));
+            }
+            key = key.checked_sub(1)?;
+            index = u16::MAX;
+        }
+        Some(util::join(key, index))
+    }
+
     /// Returns the number of integers that are <= value. rank(u32::MAX) == len()
     ///
     /// # Examples
@@ -578,6 +1145,94 @@ impl RoaringBitmap {
 
         None
     }
+
+    /// Converts each container to whichever of the array, bitmap or run representations
+    /// takes the least space, matching the `runOptimize` of the other implementations.
+    ///
+    /// Returns `true` if the bitmap contains at least one run container afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..100_000).collect();
+    /// let before = rb.serialized_size();
+    /// assert!(rb.run_optimize());
+    /// assert!(rb.serialized_size() < before);
+    /// assert_eq!(rb.len(), 100_000);
+    /// ```
+    pub fn run_optimize(&mut self) -> bool {
+        let mut has_run = false;
+        for container in &mut self.containers {
+            has_run |= container.optimize();
+        }
+        has_run
+    }
+
+    /// Converts every run container back to an array or bitmap container.
+    ///
+    /// Returns `true` if at least one container was changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..100_000).collect();
+    /// rb.run_optimize();
+    /// assert!(rb.remove_run_compression());
+    /// assert!(!rb.remove_run_compression());
+    /// assert_eq!(rb.len(), 100_000);
+    /// ```
+    pub fn remove_run_compression(&mut self) -> bool {
+        let mut changed = false;
+        for container in &mut self.containers {
+            changed |= container.remove_run_compression();
+        }
+        changed
+    }
+
+    /// Returns the number of bytes allocated on the heap by this bitmap, including the
+    /// spare capacity of the container vector and of the array and run containers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..4000).collect();
+    /// let usage = rb.memory_usage();
+    /// assert!(usage >= 4000 * 2);
+    ///
+    /// rb.remove_range(10..);
+    /// assert_eq!(rb.memory_usage(), usage);
+    /// rb.shrink_to_fit();
+    /// assert!(rb.memory_usage() < usage);
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        let containers = self.containers.capacity() * mem::size_of::<Container>();
+        containers + self.containers.iter().map(|c| c.store.heap_size()).sum::<usize>()
+    }
+
+    /// Releases the spare capacity of the container vector and of the array and run containers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..4000).collect();
+    /// rb.remove_range(10..);
+    /// rb.shrink_to_fit();
+    /// assert!(rb.iter().eq(0..10));
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.containers.shrink_to_fit();
+        for container in &mut self.containers {
+            container.store.shrink_to_fit();
+        }
+    }
 }
 
 impl Default for RoaringBitmap {
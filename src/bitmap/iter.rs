@@ -1,33 +1,393 @@
 use std::iter::{self, FromIterator};
+use std::ops::{RangeBounds, RangeInclusive};
 use std::{slice, vec};
 
-use super::container::Container;
+use super::container::{self, Container};
+use super::util;
 use crate::{NonSortedIntegers, RoaringBitmap};
 
 /// An iterator for `RoaringBitmap`.
 pub struct Iter<'a> {
-    inner: iter::Flatten<slice::Iter<'a, Container>>,
+    front: Option<container::Iter<'a>>,
+    containers: slice::Iter<'a, Container>,
+    back: Option<container::Iter<'a>>,
     size_hint: u64,
 }
 
+/// An iterator over the values of a `RoaringBitmap` that are within a range.
+pub struct Range<'a> {
+    inner: Iter<'a>,
+}
+
+/// An iterator over the maximal runs of consecutive values of a `RoaringBitmap`.
+pub struct Ranges<'a> {
+    containers: InnerRanges<'a>,
+    // The ranges taken from the containers that may be merged with the following ones
+    front: Option<RangeInclusive<u32>>,
+    back: Option<RangeInclusive<u32>>,
+}
+
+type InnerRanges<'a> = iter::FlatMap<
+    slice::Iter<'a, Container>,
+    container::Ranges<'a>,
+    fn(&'a Container) -> container::Ranges<'a>,
+>;
+
 /// An iterator for `RoaringBitmap`.
 pub struct IntoIter {
-    inner: iter::Flatten<vec::IntoIter<Container>>,
+    front: Option<container::Iter<'static>>,
+    containers: vec::IntoIter<Container>,
+    back: Option<container::Iter<'static>>,
     size_hint: u64,
 }
 
 impl Iter<'_> {
-    fn new(containers: &[Container]) -> Iter {
+    fn new(containers: &[Container]) -> Iter<'_> {
         let size_hint = containers.iter().map(|c| c.len()).sum();
-        Iter { inner: containers.iter().flatten(), size_hint }
+        Iter { front: None, containers: containers.iter(), back: None, size_hint }
+    }
+
+    /// Writes the next values of the iterator into `buf` and returns the number of
+    /// written values, which is lower than the length of `buf` only when the iterator
+    /// is exhausted. The values are copied a container at a time, which is faster than
+    /// calling `next` for each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let bitmap: RoaringBitmap = (0..10).collect();
+    /// let mut iter = bitmap.iter();
+    /// let mut buf = [0; 4];
+    ///
+    /// assert_eq!(iter.next_many(&mut buf), 4);
+    /// assert_eq!(buf, [0, 1, 2, 3]);
+    /// assert_eq!(iter.next(), Some(4));
+    /// assert_eq!(iter.next_many(&mut buf), 4);
+    /// assert_eq!(buf, [5, 6, 7, 8]);
+    /// assert_eq!(iter.next_many(&mut buf), 1);
+    /// assert_eq!(buf[0], 9);
+    /// ```
+    pub fn next_many(&mut self, buf: &mut [u32]) -> usize {
+        let n = next_many(&mut self.front, &mut self.containers, &mut self.back, buf);
+        self.size_hint = self.size_hint.saturating_sub(n as u64);
+        n
+    }
+
+    /// Advance the iterator to the first position where the item is greater than or equal
+    /// to `n`. The containers before `n` are skipped without being iterated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let bitmap: RoaringBitmap = [1, 2, 3, 100_000, 100_001].into_iter().collect();
+    /// let mut iter = bitmap.iter();
+    ///
+    /// iter.advance_to(3);
+    /// assert_eq!(iter.next(), Some(3));
+    /// iter.advance_to(50_000);
+    /// assert_eq!(iter.next(), Some(100_000));
+    /// // The iterator never goes backward
+    /// iter.advance_to(2);
+    /// assert_eq!(iter.next(), Some(100_001));
+    /// ```
+    pub fn advance_to(&mut self, n: u32) {
+        let skipped =
+            advance_to(&mut self.front, &mut self.containers, &mut self.back, n, |c| c.as_slice());
+        self.size_hint = self.size_hint.saturating_sub(skipped);
+    }
+
+    /// Advance the back of the iterator to the last position where the item is lower than
+    /// or equal to `n`. The containers after `n` are skipped without being iterated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let bitmap: RoaringBitmap = [1, 2, 3, 100_000, 100_001].into_iter().collect();
+    /// let mut iter = bitmap.iter();
+    ///
+    /// iter.advance_back_to(100_000);
+    /// assert_eq!(iter.next_back(), Some(100_000));
+    /// iter.advance_back_to(50_000);
+    /// assert_eq!(iter.next_back(), Some(3));
+    /// ```
+    pub fn advance_back_to(&mut self, n: u32) {
+        let skipped =
+            advance_back_to(&mut self.front, &mut self.containers, &mut self.back, n, |c| {
+                c.as_slice()
+            });
+        self.size_hint = self.size_hint.saturating_sub(skipped);
+    }
+}
+
+impl<'a> Range<'a> {
+    fn new<R: RangeBounds<u32>>(bitmap: &'a RoaringBitmap, range: R) -> Range<'a> {
+        let range = match util::convert_range_to_inclusive(range) {
+            Some(range) => range,
+            None => return Range { inner: Iter::new(&[]) },
+        };
+        let (start_key, _) = util::split(*range.start());
+        let (end_key, _) = util::split(*range.end());
+        let containers = &bitmap.containers;
+        let first = containers.partition_point(|c| c.key < start_key);
+        let last = containers.partition_point(|c| c.key <= end_key);
+
+        let mut inner = Iter::new(&containers[first..last]);
+        inner.advance_to(*range.start());
+        inner.advance_back_to(*range.end());
+        inner.size_hint = bitmap.range_cardinality(range);
+        Range { inner }
+    }
+}
+
+impl Ranges<'_> {
+    fn new(containers: &[Container]) -> Ranges<'_> {
+        let containers = containers.iter().flat_map(Container::ranges as _);
+        Ranges { containers, front: None, back: None }
     }
 }
 
 impl IntoIter {
     fn new(containers: Vec<Container>) -> IntoIter {
         let size_hint = containers.iter().map(|c| c.len()).sum();
-        IntoIter { inner: containers.into_iter().flatten(), size_hint }
+        IntoIter { front: None, containers: containers.into_iter(), back: None, size_hint }
+    }
+
+    /// Writes the next values of the iterator into `buf` and returns the number of
+    /// written values, which is lower than the length of `buf` only when the iterator
+    /// is exhausted. The values are copied a container at a time, which is faster than
+    /// calling `next` for each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let bitmap: RoaringBitmap = (0..10).collect();
+    /// let mut iter = bitmap.into_iter();
+    /// let mut buf = [0; 4];
+    ///
+    /// assert_eq!(iter.next_many(&mut buf), 4);
+    /// assert_eq!(buf, [0, 1, 2, 3]);
+    /// assert_eq!(iter.next(), Some(4));
+    /// assert_eq!(iter.next_many(&mut buf), 4);
+    /// assert_eq!(buf, [5, 6, 7, 8]);
+    /// assert_eq!(iter.next_many(&mut buf), 1);
+    /// assert_eq!(buf[0], 9);
+    /// ```
+    pub fn next_many(&mut self, buf: &mut [u32]) -> usize {
+        let n = next_many(&mut self.front, &mut self.containers, &mut self.back, buf);
+        self.size_hint = self.size_hint.saturating_sub(n as u64);
+        n
+    }
+
+    /// Advance the iterator to the first position where the item is greater than or equal
+    /// to `n`. The containers before `n` are skipped without being iterated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let bitmap: RoaringBitmap = [1, 2, 3, 100_000, 100_001].into_iter().collect();
+    /// let mut iter = bitmap.into_iter();
+    ///
+    /// iter.advance_to(50_000);
+    /// assert_eq!(iter.next(), Some(100_000));
+    /// ```
+    pub fn advance_to(&mut self, n: u32) {
+        let skipped =
+            advance_to(&mut self.front, &mut self.containers, &mut self.back, n, |c| c.as_slice());
+        self.size_hint = self.size_hint.saturating_sub(skipped);
+    }
+
+    /// Advance the back of the iterator to the last position where the item is lower than
+    /// or equal to `n`. The containers after `n` are skipped without being iterated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let bitmap: RoaringBitmap = [1, 2, 3, 100_000, 100_001].into_iter().collect();
+    /// let mut iter = bitmap.into_iter();
+    ///
+    /// iter.advance_back_to(50_000);
+    /// assert_eq!(iter.next_back(), Some(3));
+    /// ```
+    pub fn advance_back_to(&mut self, n: u32) {
+        let skipped =
+            advance_back_to(&mut self.front, &mut self.containers, &mut self.back, n, |c| {
+                c.as_slice()
+            });
+        self.size_hint = self.size_hint.saturating_sub(skipped);
+    }
+}
+
+/// Returns the next value of the flattened containers, like `iter::Flatten` does.
+fn next<'a, I>(
+    front: &mut Option<container::Iter<'a>>,
+    containers: &mut I,
+    back: &mut Option<container::Iter<'a>>,
+) -> Option<u32>
+where
+    I: Iterator,
+    I::Item: IntoIterator<IntoIter = container::Iter<'a>>,
+{
+    loop {
+        if let Some(value) = front.as_mut().and_then(Iterator::next) {
+            return Some(value);
+        }
+        match containers.next() {
+            Some(container) => *front = Some(container.into_iter()),
+            None => return back.as_mut().and_then(Iterator::next),
+        }
+    }
+}
+
+/// Fills `buf` with the next values from the front of the flattened containers,
+/// moving on to the following containers as needed. Returns the number of values written.
+fn next_many<'a, I>(
+    front: &mut Option<container::Iter<'a>>,
+    containers: &mut I,
+    back: &mut Option<container::Iter<'a>>,
+    buf: &mut [u32],
+) -> usize
+where
+    I: Iterator,
+    I::Item: IntoIterator<IntoIter = container::Iter<'a>>,
+{
+    let mut n = 0;
+    loop {
+        if let Some(iter) = front {
+            n += iter.next_many(&mut buf[n..]);
+            if n == buf.len() {
+                return n;
+            }
+        }
+        match containers.next() {
+            Some(container) => *front = Some(container.into_iter()),
+            None => return n + back.as_mut().map_or(0, |iter| iter.next_many(&mut buf[n..])),
+        }
+    }
+}
+
+/// Returns the next value from the back of the flattened containers.
+fn next_back<'a, I>(
+    front: &mut Option<container::Iter<'a>>,
+    containers: &mut I,
+    back: &mut Option<container::Iter<'a>>,
+) -> Option<u32>
+where
+    I: DoubleEndedIterator,
+    I::Item: IntoIterator<IntoIter = container::Iter<'a>>,
+{
+    loop {
+        if let Some(value) = back.as_mut().and_then(DoubleEndedIterator::next_back) {
+            return Some(value);
+        }
+        match containers.next_back() {
+            Some(container) => *back = Some(container.into_iter()),
+            None => return front.as_mut().and_then(DoubleEndedIterator::next_back),
+        }
+    }
+}
+
+/// Skips the values lower than `n` of the flattened containers, the remaining containers
+/// are binary searched. Returns the number of skipped values.
+fn advance_to<'a, I, F>(
+    front: &mut Option<container::Iter<'a>>,
+    containers: &mut I,
+    back: &mut Option<container::Iter<'a>>,
+    n: u32,
+    as_slice: F,
+) -> u64
+where
+    I: Iterator,
+    I::Item: IntoIterator<IntoIter = container::Iter<'a>>,
+    F: Fn(&I) -> &[Container],
+{
+    let (key, index) = util::split(n);
+    let mut skipped = 0;
+    if let Some(iter) = front {
+        if iter.key > key {
+            return 0;
+        } else if iter.key == key {
+            return iter.advance_to(index);
+        }
+        skipped += iter.skip_all();
+        *front = None;
+    }
+
+    let remaining = as_slice(containers);
+    let i = remaining.partition_point(|container| container.key < key);
+    skipped += remaining[..i].iter().map(Container::len).sum::<u64>();
+    match containers.nth(i) {
+        Some(container) => {
+            let mut iter = container.into_iter();
+            if iter.key == key {
+                skipped += iter.advance_to(index);
+            }
+            *front = Some(iter);
+        }
+        None => match back {
+            Some(iter) if iter.key < key => skipped += iter.skip_all(),
+            Some(iter) if iter.key == key => skipped += iter.advance_to(index),
+            _ => (),
+        },
+    }
+    skipped
+}
+
+/// Skips the values greater than `n` from the back of the flattened containers, the
+/// remaining containers are binary searched. Returns the number of skipped values.
+fn advance_back_to<'a, I, F>(
+    front: &mut Option<container::Iter<'a>>,
+    containers: &mut I,
+    back: &mut Option<container::Iter<'a>>,
+    n: u32,
+    as_slice: F,
+) -> u64
+where
+    I: DoubleEndedIterator,
+    I::Item: IntoIterator<IntoIter = container::Iter<'a>>,
+    F: Fn(&I) -> &[Container],
+{
+    let (key, index) = util::split(n);
+    let mut skipped = 0;
+    if let Some(iter) = back {
+        if iter.key < key {
+            return 0;
+        } else if iter.key == key {
+            return iter.advance_back_to(index);
+        }
+        skipped += iter.skip_all();
+        *back = None;
+    }
+
+    let remaining = as_slice(containers);
+    let i = remaining.partition_point(|container| container.key <= key);
+    skipped += remaining[i..].iter().map(Container::len).sum::<u64>();
+    match containers.nth_back(remaining.len() - i) {
+        Some(container) => {
+            let mut iter = container.into_iter();
+            if iter.key == key {
+                skipped += iter.advance_back_to(index);
+            }
+            *back = Some(iter);
+        }
+        None => match front {
+            Some(iter) if iter.key > key => skipped += iter.skip_all(),
+            Some(iter) if iter.key == key => skipped += iter.advance_back_to(index),
+            _ => (),
+        },
     }
+    skipped
 }
 
 impl Iterator for Iter<'_> {
@@ -35,7 +395,7 @@ impl Iterator for Iter<'_> {
 
     fn next(&mut self) -> Option<u32> {
         self.size_hint = self.size_hint.saturating_sub(1);
-        self.inner.next()
+        next(&mut self.front, &mut self.containers, &mut self.back)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -50,7 +410,7 @@ impl Iterator for Iter<'_> {
 impl DoubleEndedIterator for Iter<'_> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.size_hint = self.size_hint.saturating_sub(1);
-        self.inner.next_back()
+        next_back(&mut self.front, &mut self.containers, &mut self.back)
     }
 }
 
@@ -61,12 +421,83 @@ impl ExactSizeIterator for Iter<'_> {
     }
 }
 
+impl Iterator for Range<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Range<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for Range<'_> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl Iterator for Ranges<'_> {
+    type Item = RangeInclusive<u32>;
+
+    fn next(&mut self) -> Option<RangeInclusive<u32>> {
+        let (start, mut end) = match self.front.take().or_else(|| self.containers.next()) {
+            Some(range) => range.into_inner(),
+            None => return self.back.take(),
+        };
+        for next in self.containers.by_ref() {
+            if end + 1 != *next.start() {
+                self.front = Some(next);
+                return Some(start..=end);
+            }
+            end = *next.end();
+        }
+        // The containers are exhausted but the range may continue in the back one
+        match self.back.take() {
+            Some(back) if end + 1 == *back.start() => end = *back.end(),
+            back => self.back = back,
+        }
+        Some(start..=end)
+    }
+}
+
+impl DoubleEndedIterator for Ranges<'_> {
+    fn next_back(&mut self) -> Option<RangeInclusive<u32>> {
+        let (mut start, end) = match self.back.take().or_else(|| self.containers.next_back()) {
+            Some(range) => range.into_inner(),
+            None => return self.front.take(),
+        };
+        for prev in self.containers.by_ref().rev() {
+            if *prev.end() + 1 != start {
+                self.back = Some(prev);
+                return Some(start..=end);
+            }
+            start = *prev.start();
+        }
+        // The containers are exhausted but the range may continue in the front one
+        match self.front.take() {
+            Some(front) if *front.end() + 1 == start => start = *front.start(),
+            front => self.front = front,
+        }
+        Some(start..=end)
+    }
+}
+
 impl Iterator for IntoIter {
     type Item = u32;
 
     fn next(&mut self) -> Option<u32> {
         self.size_hint = self.size_hint.saturating_sub(1);
-        self.inner.next()
+        next(&mut self.front, &mut self.containers, &mut self.back)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -81,7 +512,7 @@ impl Iterator for IntoIter {
 impl DoubleEndedIterator for IntoIter {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.size_hint = self.size_hint.saturating_sub(1);
-        self.inner.next_back()
+        next_back(&mut self.front, &mut self.containers, &mut self.back)
     }
 }
 
@@ -108,9 +539,54 @@ impl RoaringBitmap {
     /// assert_eq!(iter.next(), Some(2));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         Iter::new(&self.containers)
     }
+
+    /// Iterator over the maximal runs of consecutive values stored in the RoaringBitmap,
+    /// guarantees ranges are ordered by value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let bitmap: RoaringBitmap = (1..3).chain(5..70_000).chain([100_000]).collect();
+    /// let mut ranges = bitmap.iter_ranges();
+    ///
+    /// assert_eq!(ranges.next(), Some(1..=2));
+    /// assert_eq!(ranges.next_back(), Some(100_000..=100_000));
+    /// assert_eq!(ranges.next(), Some(5..=69_999));
+    /// assert_eq!(ranges.next(), None);
+    /// ```
+    pub fn iter_ranges(&self) -> Ranges<'_> {
+        Ranges::new(&self.containers)
+    }
+
+    /// Iterator over the values stored in the RoaringBitmap that are within the given range,
+    /// guarantees values are ordered by value. The iterator starts directly at the first value
+    /// of the range, the values before it are not iterated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let bitmap: RoaringBitmap = (1..10).chain(100_000..100_005).collect();
+    /// let mut range = bitmap.range(5..100_002);
+    ///
+    /// assert_eq!(range.len(), 7);
+    /// assert_eq!(range.next(), Some(5));
+    /// assert_eq!(range.next_back(), Some(100_001));
+    /// assert_eq!(range.next_back(), Some(100_000));
+    /// assert_eq!(range.next_back(), Some(9));
+    /// ```
+    pub fn range<R>(&self, range: R) -> Range<'_>
+    where
+        R: RangeBounds<u32>,
+    {
+        Range::new(self, range)
+    }
 }
 
 impl<'a> IntoIterator for &'a RoaringBitmap {
@@ -133,7 +609,7 @@ impl IntoIterator for RoaringBitmap {
 
 impl<const N: usize> From<[u32; N]> for RoaringBitmap {
     fn from(arr: [u32; N]) -> Self {
-        RoaringBitmap::from_iter(arr.into_iter())
+        RoaringBitmap::from_iter(arr)
     }
 }
 
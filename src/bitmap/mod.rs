@@ -15,10 +15,16 @@ mod ops;
 #[cfg(feature = "serde")]
 mod serde;
 mod serialization;
+mod statistics;
+mod view;
 
 use self::cmp::Pairs;
 pub use self::iter::IntoIter;
 pub use self::iter::Iter;
+pub use self::iter::{Range, Ranges};
+pub use self::serialization::{DeserializeOptions, ValidationReport};
+pub use self::statistics::Statistics;
+pub use self::view::{RoaringBitmapView, ViewIter};
 
 /// A compressed bitmap using the [Roaring bitmap compression scheme](https://roaringbitmap.org/).
 ///
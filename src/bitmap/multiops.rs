@@ -1,9 +1,11 @@
 use std::{
-    borrow::Cow,
-    cmp::Reverse,
+    borrow::{Borrow, Cow},
+    cmp::{Ordering, Reverse},
+    collections::{binary_heap::PeekMut, BinaryHeap},
     convert::Infallible,
     mem,
-    ops::{BitOrAssign, BitXorAssign},
+    ops::BitXorAssign,
+    slice, vec,
 };
 
 use retain_mut::RetainMut;
@@ -25,6 +27,7 @@ where
     I: IntoIterator<Item = RoaringBitmap>,
 {
     type Output = RoaringBitmap;
+    type LenOutput = u64;
 
     fn union(self) -> Self::Output {
         try_multi_or_owned(self.into_iter().map(Ok::<_, Infallible>)).unwrap()
@@ -41,6 +44,32 @@ where
     fn symmetric_difference(self) -> Self::Output {
         try_multi_xor_owned(self.into_iter().map(Ok::<_, Infallible>)).unwrap()
     }
+
+    fn at_least(self, k: usize) -> Self::Output {
+        try_with_containers_owned(self.into_iter().map(Ok::<_, Infallible>), |containers, _| {
+            multi_at_least(containers, k)
+        })
+        .unwrap()
+    }
+
+    fn union_len(self) -> Self::LenOutput {
+        try_with_containers_owned(self.into_iter().map(Ok::<_, Infallible>), |containers, _| {
+            multi_union_len(containers)
+        })
+        .unwrap()
+    }
+
+    fn intersection_len(self) -> Self::LenOutput {
+        try_with_containers_owned(self.into_iter().map(Ok::<_, Infallible>), multi_intersection_len)
+            .unwrap()
+    }
+
+    fn symmetric_difference_len(self) -> Self::LenOutput {
+        try_with_containers_owned(self.into_iter().map(Ok::<_, Infallible>), |containers, _| {
+            multi_symmetric_difference_len(containers)
+        })
+        .unwrap()
+    }
 }
 
 impl<I, E> MultiOps<Result<RoaringBitmap, E>> for I
@@ -48,6 +77,7 @@ where
     I: IntoIterator<Item = Result<RoaringBitmap, E>>,
 {
     type Output = Result<RoaringBitmap, E>;
+    type LenOutput = Result<u64, E>;
 
     fn union(self) -> Self::Output {
         try_multi_or_owned(self)
@@ -64,6 +94,22 @@ where
     fn symmetric_difference(self) -> Self::Output {
         try_multi_xor_owned(self)
     }
+
+    fn at_least(self, k: usize) -> Self::Output {
+        try_with_containers_owned(self, |containers, _| multi_at_least(containers, k))
+    }
+
+    fn union_len(self) -> Self::LenOutput {
+        try_with_containers_owned(self, |containers, _| multi_union_len(containers))
+    }
+
+    fn intersection_len(self) -> Self::LenOutput {
+        try_with_containers_owned(self, multi_intersection_len)
+    }
+
+    fn symmetric_difference_len(self) -> Self::LenOutput {
+        try_with_containers_owned(self, |containers, _| multi_symmetric_difference_len(containers))
+    }
 }
 
 impl<'a, I> MultiOps<&'a RoaringBitmap> for I
@@ -71,6 +117,7 @@ where
     I: IntoIterator<Item = &'a RoaringBitmap>,
 {
     type Output = RoaringBitmap;
+    type LenOutput = u64;
 
     fn union(self) -> Self::Output {
         try_multi_or_ref(self.into_iter().map(Ok::<_, Infallible>)).unwrap()
@@ -87,6 +134,32 @@ where
     fn symmetric_difference(self) -> Self::Output {
         try_multi_xor_ref(self.into_iter().map(Ok::<_, Infallible>)).unwrap()
     }
+
+    fn at_least(self, k: usize) -> Self::Output {
+        try_with_containers_ref(self.into_iter().map(Ok::<_, Infallible>), |containers, _| {
+            multi_at_least(containers, k)
+        })
+        .unwrap()
+    }
+
+    fn union_len(self) -> Self::LenOutput {
+        try_with_containers_ref(self.into_iter().map(Ok::<_, Infallible>), |containers, _| {
+            multi_union_len(containers)
+        })
+        .unwrap()
+    }
+
+    fn intersection_len(self) -> Self::LenOutput {
+        try_with_containers_ref(self.into_iter().map(Ok::<_, Infallible>), multi_intersection_len)
+            .unwrap()
+    }
+
+    fn symmetric_difference_len(self) -> Self::LenOutput {
+        try_with_containers_ref(self.into_iter().map(Ok::<_, Infallible>), |containers, _| {
+            multi_symmetric_difference_len(containers)
+        })
+        .unwrap()
+    }
 }
 
 impl<'a, I, E: 'a> MultiOps<Result<&'a RoaringBitmap, E>> for I
@@ -94,6 +167,7 @@ where
     I: IntoIterator<Item = Result<&'a RoaringBitmap, E>>,
 {
     type Output = Result<RoaringBitmap, E>;
+    type LenOutput = Result<u64, E>;
 
     fn union(self) -> Self::Output {
         try_multi_or_ref(self)
@@ -110,6 +184,22 @@ where
     fn symmetric_difference(self) -> Self::Output {
         try_multi_xor_ref(self)
     }
+
+    fn at_least(self, k: usize) -> Self::Output {
+        try_with_containers_ref(self, |containers, _| multi_at_least(containers, k))
+    }
+
+    fn union_len(self) -> Self::LenOutput {
+        try_with_containers_ref(self, |containers, _| multi_union_len(containers))
+    }
+
+    fn intersection_len(self) -> Self::LenOutput {
+        try_with_containers_ref(self, multi_intersection_len)
+    }
+
+    fn symmetric_difference_len(self) -> Self::LenOutput {
+        try_with_containers_ref(self, |containers, _| multi_symmetric_difference_len(containers))
+    }
 }
 
 #[inline]
@@ -125,7 +215,7 @@ fn try_multi_and_owned<E>(
     let mut start = start.into_iter();
 
     if let Some(mut lhs) = start.next() {
-        for rhs in start.into_iter().map(Ok).chain(iter) {
+        for rhs in start.map(Ok).chain(iter) {
             if lhs.is_empty() {
                 return Ok(lhs);
             }
@@ -151,7 +241,7 @@ fn try_multi_and_ref<'a, E>(
     let mut start = start.into_iter();
 
     if let Some(mut lhs) = start.next().cloned() {
-        for rhs in start.into_iter().map(Ok).chain(iter) {
+        for rhs in start.map(Ok).chain(iter) {
             if lhs.is_empty() {
                 return Ok(lhs);
             }
@@ -225,11 +315,14 @@ fn try_multi_or_owned<E>(
         return Ok(RoaringBitmap::new());
     };
 
-    for bitmap in start.into_iter().map(Ok).chain(iter) {
-        merge_container_owned(&mut containers, bitmap?.containers, BitOrAssign::bitor_assign);
+    // The bitmap stores are unioned lazily, their cardinality is only
+    // recomputed once every bitmap has been merged.
+    for bitmap in start.map(Ok).chain(iter) {
+        lazy_or_container_owned(&mut containers, bitmap?.containers);
     }
 
     RetainMut::retain_mut(&mut containers, |container| {
+        container.store.repair_len();
         if container.len() > 0 {
             container.ensure_correct_store();
             true
@@ -241,6 +334,27 @@ fn try_multi_or_owned<E>(
     Ok(RoaringBitmap { containers })
 }
 
+fn lazy_or_container_owned(lhs: &mut Vec<Container>, rhs: Vec<Container>) {
+    for mut rhs in rhs {
+        match lhs.binary_search_by_key(&rhs.key, |c| c.key) {
+            Err(loc) => lhs.insert(loc, rhs),
+            Ok(loc) => {
+                let lhs = &mut lhs[loc];
+                match (&lhs.store, &rhs.store) {
+                    // Unioning arrays one pair at a time reallocates them again and again,
+                    // the colliding arrays are promoted and demoted once at the end instead
+                    (Store::Array(..), Store::Array(..)) => lhs.store = lhs.store.to_bitmap(),
+                    (Store::Array(..), Store::Bitmap(..)) | (Store::Run(..), Store::Bitmap(..)) => {
+                        mem::swap(lhs, &mut rhs)
+                    }
+                    _ => (),
+                };
+                lhs.store.lazy_or(&rhs.store);
+            }
+        }
+    }
+}
+
 #[inline]
 fn try_multi_xor_owned<E>(
     bitmaps: impl IntoIterator<Item = Result<RoaringBitmap, E>>,
@@ -322,26 +436,71 @@ fn try_multi_or_ref<'a, E: 'a>(
         }
     };
 
-    // Phase 2: Operate on the remaining containers
-    for bitmap in start.into_iter().map(Ok).chain(iter) {
-        merge_container_ref(&mut containers, &bitmap?.containers, |a, b| *a |= b);
+    // Phase 2: Operate on the remaining containers, the bitmap stores are unioned lazily
+    for bitmap in start.map(Ok).chain(iter) {
+        lazy_or_container_ref(&mut containers, &bitmap?.containers);
     }
 
     // Phase 3: Clean up
     let containers: Vec<_> = containers
         .into_iter()
-        .filter(|container| container.len() > 0)
-        .map(|c| {
-            // Any borrowed bitmaps or arrays left over get cloned here
-            let mut container = c.into_owned();
-            container.ensure_correct_store();
-            container
+        .filter_map(|c| {
+            let mut container = match c {
+                // Only the owned containers went through a lazy union
+                Cow::Owned(mut container) => {
+                    container.store.repair_len();
+                    container
+                }
+                // Any borrowed bitmaps or arrays left over get cloned here
+                Cow::Borrowed(container) => container.clone(),
+            };
+            if container.len() > 0 {
+                container.ensure_correct_store();
+                Some(container)
+            } else {
+                None
+            }
         })
         .collect();
 
     Ok(RoaringBitmap { containers })
 }
 
+fn lazy_or_container_ref<'a>(containers: &mut Vec<Cow<'a, Container>>, rhs: &'a [Container]) {
+    for rhs in rhs {
+        match containers.binary_search_by_key(&rhs.key, |c| c.key) {
+            Err(loc) => {
+                // A container not currently in containers. Borrow it.
+                containers.insert(loc, Cow::Borrowed(rhs))
+            }
+            Ok(loc) => {
+                // A container that is in containers. Operate on it.
+                let lhs = &mut containers[loc];
+                match (&lhs.store, &rhs.store) {
+                    (Store::Array(..), Store::Array(..)) => {
+                        // We had borrowed an array. Without cloning it, create a new bitmap
+                        // Add all the elements to the new bitmap
+                        let mut store = lhs.store.to_bitmap();
+                        store.lazy_or(&rhs.store);
+                        *lhs = Cow::Owned(Container { key: lhs.key, store });
+                    }
+                    (Store::Array(..), Store::Bitmap(..)) | (Store::Run(..), Store::Bitmap(..)) => {
+                        // Copy the rhs bitmap, add lhs to it
+                        let mut store = rhs.store.clone();
+                        store.lazy_or(&lhs.store);
+                        *lhs = Cow::Owned(Container { key: lhs.key, store });
+                    }
+                    _ => {
+                        // This might be a owned or borrowed container.
+                        // If it was borrowed it will clone-on-write
+                        lhs.to_mut().store.lazy_or(&rhs.store);
+                    }
+                };
+            }
+        }
+    }
+}
+
 #[inline]
 fn try_multi_xor_ref<'a, E: 'a>(
     bitmaps: impl IntoIterator<Item = Result<&'a RoaringBitmap, E>>,
@@ -411,8 +570,10 @@ fn merge_container_ref<'a>(
                         op(&mut store, &lhs.store);
                         *lhs = Cow::Owned(Container { key: lhs.key, store });
                     }
-                    (Store::Bitmap(..), _) => {
-                        // This might be a owned or borrowed bitmap.
+                    (Store::Bitmap(..), _)
+                    | (Store::Run(..), _)
+                    | (Store::Array(..), Store::Run(..)) => {
+                        // This might be a owned or borrowed container.
                         // If it was borrowed it will clone-on-write
                         op(&mut lhs.to_mut().store, &rhs.store);
                     }
@@ -422,6 +583,245 @@ fn merge_container_ref<'a>(
     }
 }
 
+/// Calls `f` with an iterator over the containers of each bitmap and the number of bitmaps.
+///
+/// The bitmaps are all pulled from the iterator first: the containers of a key are only
+/// known once every bitmap has been seen.
+#[inline]
+fn try_with_containers_owned<E, R>(
+    bitmaps: impl IntoIterator<Item = Result<RoaringBitmap, E>>,
+    f: impl FnOnce(Vec<vec::IntoIter<Container>>, usize) -> R,
+) -> Result<R, E> {
+    let iters = bitmaps
+        .into_iter()
+        .map(|bitmap| bitmap.map(|bitmap| bitmap.containers.into_iter()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let count = iters.len();
+    Ok(f(iters, count))
+}
+
+#[inline]
+fn try_with_containers_ref<'a, E: 'a, R>(
+    bitmaps: impl IntoIterator<Item = Result<&'a RoaringBitmap, E>>,
+    f: impl FnOnce(Vec<slice::Iter<'a, Container>>, usize) -> R,
+) -> Result<R, E> {
+    let iters = bitmaps
+        .into_iter()
+        .map(|bitmap| bitmap.map(|bitmap| bitmap.containers.iter()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let count = iters.len();
+    Ok(f(iters, count))
+}
+
+/// Calls `f` with the containers of each key, in increasing key order.
+///
+/// The sorted containers of the bitmaps are merged as they are iterated,
+/// only the containers of the current key are gathered at a time.
+fn for_each_key<C, I>(iters: Vec<I>, mut f: impl FnMut(&[C]))
+where
+    C: Borrow<Container>,
+    I: Iterator<Item = C>,
+{
+    let mut heap: BinaryHeap<_> = iters
+        .into_iter()
+        .filter_map(|mut iter| {
+            iter.next().map(|container| PeekedContainer {
+                key: container.borrow().key,
+                container,
+                iter,
+            })
+        })
+        .collect();
+
+    let mut group: Vec<C> = Vec::new();
+    while let Some(mut peek) = heap.peek_mut() {
+        let container = match peek.iter.next() {
+            Some(next_container) => {
+                peek.key = next_container.borrow().key;
+                mem::replace(&mut peek.container, next_container)
+            }
+            None => PeekMut::pop(peek).container,
+        };
+
+        if let Some(first) = group.first() {
+            if first.borrow().key != container.borrow().key {
+                f(&group);
+                group.clear();
+            }
+        }
+
+        group.push(container);
+    }
+
+    if !group.is_empty() {
+        f(&group);
+    }
+}
+
+struct PeekedContainer<C, I> {
+    key: u16,
+    container: C,
+    iter: I,
+}
+
+impl<C, I> Ord for PeekedContainer<C, I> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key).reverse()
+    }
+}
+
+impl<C, I> PartialOrd for PeekedContainer<C, I> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C, I> Eq for PeekedContainer<C, I> {}
+
+impl<C, I> PartialEq for PeekedContainer<C, I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+fn multi_at_least<C, I>(iters: Vec<I>, k: usize) -> RoaringBitmap
+where
+    C: Borrow<Container>,
+    I: Iterator<Item = C>,
+{
+    let k = k.max(1);
+
+    // The values are only counted for the keys with at least k containers
+    // that are neither a union nor an intersection of the containers.
+    let mut counts: Vec<u32> = Vec::new();
+    let mut result = Vec::new();
+    for_each_key(iters, |group| {
+        if group.len() < k {
+            return;
+        }
+
+        let mut container = if k == 1 {
+            lazy_union(group)
+        } else if k == group.len() {
+            group[1..].iter().fold(group[0].borrow().clone(), |mut acc, other| {
+                acc &= other.borrow();
+                acc
+            })
+        } else {
+            if counts.is_empty() {
+                counts = vec![0; 1 << 16];
+            }
+            for other in group {
+                for index in &other.borrow().store {
+                    counts[index as usize] += 1;
+                }
+            }
+            let mut container = Container::new(group[0].borrow().key);
+            for (index, count) in counts.iter_mut().enumerate() {
+                if *count as usize >= k {
+                    container.push_unchecked(index as u16);
+                }
+                *count = 0;
+            }
+            container
+        };
+
+        if container.len() > 0 {
+            container.ensure_correct_store();
+            result.push(container);
+        }
+    });
+
+    RoaringBitmap { containers: result }
+}
+
+/// Unions a group of containers with the same key, the cardinality
+/// of the resulting container is only computed once at the end.
+fn lazy_union<C: Borrow<Container>>(group: &[C]) -> Container {
+    match group {
+        [] => unreachable!("a group contains at least one container"),
+        [container] => container.borrow().clone(),
+        [first, rest @ ..] => {
+            let mut store = first.borrow().store.to_bitmap();
+            rest.iter().for_each(|other| store.lazy_or(&other.borrow().store));
+            store.repair_len();
+            Container { key: first.borrow().key, store }
+        }
+    }
+}
+
+// The cardinality operations compute the result of each key one after the other,
+// at most one container is allocated at a time.
+
+fn multi_union_len<C, I>(iters: Vec<I>) -> u64
+where
+    C: Borrow<Container>,
+    I: Iterator<Item = C>,
+{
+    let mut len = 0;
+    for_each_key(iters, |group| {
+        len += match group {
+            [] => 0,
+            [container] => container.borrow().len(),
+            [lhs, rhs] => {
+                let (lhs, rhs) = (lhs.borrow(), rhs.borrow());
+                lhs.len() + rhs.len() - lhs.intersection_len(rhs)
+            }
+            _ => lazy_union(group).len(),
+        }
+    });
+    len
+}
+
+fn multi_intersection_len<C, I>(iters: Vec<I>, count: usize) -> u64
+where
+    C: Borrow<Container>,
+    I: Iterator<Item = C>,
+{
+    let mut len = 0;
+    for_each_key(iters, |group| {
+        // A key is in the intersection only if every bitmap has a container for it
+        if group.len() < count {
+            return;
+        }
+        len += match group {
+            [] => 0,
+            [container] => container.borrow().len(),
+            [lhs, rhs] => lhs.borrow().intersection_len(rhs.borrow()),
+            [first, rest @ ..] => {
+                let mut container = first.borrow().clone();
+                rest.iter().for_each(|other| container &= other.borrow());
+                container.len()
+            }
+        }
+    });
+    len
+}
+
+fn multi_symmetric_difference_len<C, I>(iters: Vec<I>) -> u64
+where
+    C: Borrow<Container>,
+    I: Iterator<Item = C>,
+{
+    let mut len = 0;
+    for_each_key(iters, |group| {
+        len += match group {
+            [] => 0,
+            [container] => container.borrow().len(),
+            [lhs, rhs] => {
+                let (lhs, rhs) = (lhs.borrow(), rhs.borrow());
+                lhs.len() + rhs.len() - 2 * lhs.intersection_len(rhs)
+            }
+            [first, rest @ ..] => {
+                let mut container = first.borrow().clone();
+                rest.iter().for_each(|other| container ^= other.borrow());
+                container.len()
+            }
+        }
+    });
+    len
+}
+
 #[inline]
 fn collect_starting_elements<I, El, Er>(iter: I) -> Result<Vec<El>, Er>
 where
@@ -1,17 +1,133 @@
 use bytemuck::cast_slice_mut;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::convert::{Infallible, TryFrom};
-use std::error::Error;
+use std::convert::TryFrom;
 use std::io;
+use std::ops::{Bound, RangeBounds};
 
 use super::container::Container;
-use crate::bitmap::store::{ArrayStore, BitmapStore, Store};
-use crate::RoaringBitmap;
+use super::util;
+use crate::bitmap::store::{ArrayStore, BitmapStore, Interval, RunStore, Store};
+use crate::{DeserializeError, RoaringBitmap, RoaringBitmapView};
 
-const SERIAL_COOKIE_NO_RUNCONTAINER: u32 = 12346;
-const SERIAL_COOKIE: u16 = 12347;
-// TODO: Need this once run containers are supported
-// const NO_OFFSET_THRESHOLD: u8 = 4;
+pub(crate) const SERIAL_COOKIE_NO_RUNCONTAINER: u32 = 12346;
+pub(crate) const SERIAL_COOKIE: u16 = 12347;
+pub(crate) const NO_OFFSET_THRESHOLD: usize = 4;
+
+// The constants of the CRoaring frozen format
+pub(crate) const FROZEN_COOKIE: u16 = 13766;
+pub(crate) const BITSET_CONTAINER_TYPE: u8 = 1;
+pub(crate) const ARRAY_CONTAINER_TYPE: u8 = 2;
+pub(crate) const RUN_CONTAINER_TYPE: u8 = 3;
+
+/// Limits on the resources used by [`RoaringBitmap::deserialize_with`] and
+/// [`RoaringTreemap::deserialize_with`](crate::RoaringTreemap::deserialize_with),
+/// to safely deserialize bitmaps coming from untrusted sources.
+///
+/// The limits are checked against the headers of the serialized bitmap before any
+/// memory is allocated for the containers. A treemap shares the limits between all
+/// of its bitmaps. By default nothing is limited.
+///
+/// # Examples
+///
+/// ```rust
+/// use roaring::{DeserializeError, DeserializeOptions, RoaringBitmap};
+///
+/// let rb: RoaringBitmap = (0..100_000).collect();
+/// let mut bytes = vec![];
+/// rb.serialize_into(&mut bytes).unwrap();
+///
+/// let options = DeserializeOptions::new().max_containers(1);
+/// let result = RoaringBitmap::deserialize_with(&bytes[..], &options);
+/// assert!(matches!(result, Err(DeserializeError::ContainerLimitExceeded)));
+///
+/// let options = DeserializeOptions::new().max_containers(2).max_cardinality(100_000);
+/// assert_eq!(RoaringBitmap::deserialize_with(&bytes[..], &options).unwrap(), rb);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeserializeOptions {
+    max_containers: usize,
+    max_bytes: usize,
+    max_cardinality: u64,
+    allow_runs: bool,
+}
+
+impl DeserializeOptions {
+    /// Creates options that do not limit the deserialization.
+    pub fn new() -> DeserializeOptions {
+        DeserializeOptions {
+            max_containers: usize::MAX,
+            max_bytes: usize::MAX,
+            max_cardinality: u64::MAX,
+            allow_runs: true,
+        }
+    }
+
+    /// Sets the maximum number of containers, each container holds the values
+    /// sharing the same 16 most significant bits.
+    pub fn max_containers(mut self, max: usize) -> DeserializeOptions {
+        self.max_containers = max;
+        self
+    }
+
+    /// Sets the maximum number of bytes read from the serialized data.
+    pub fn max_bytes(mut self, max: usize) -> DeserializeOptions {
+        self.max_bytes = max;
+        self
+    }
+
+    /// Sets the maximum number of values of the deserialized bitmap.
+    pub fn max_cardinality(mut self, max: u64) -> DeserializeOptions {
+        self.max_cardinality = max;
+        self
+    }
+
+    /// Sets whether run containers are accepted, they are by default.
+    pub fn allow_runs(mut self, allow: bool) -> DeserializeOptions {
+        self.allow_runs = allow;
+        self
+    }
+
+    // The limits are used as budgets that are consumed while deserializing
+
+    pub(crate) fn consume_bytes(&mut self, bytes: usize) -> Result<(), DeserializeError> {
+        self.max_bytes =
+            self.max_bytes.checked_sub(bytes).ok_or(DeserializeError::ByteLimitExceeded)?;
+        Ok(())
+    }
+
+    fn consume_containers(&mut self, containers: usize) -> Result<(), DeserializeError> {
+        self.max_containers = self
+            .max_containers
+            .checked_sub(containers)
+            .ok_or(DeserializeError::ContainerLimitExceeded)?;
+        Ok(())
+    }
+
+    fn consume_cardinality(&mut self, cardinality: u64) -> Result<(), DeserializeError> {
+        self.max_cardinality = self
+            .max_cardinality
+            .checked_sub(cardinality)
+            .ok_or(DeserializeError::CardinalityLimitExceeded)?;
+        Ok(())
+    }
+}
+
+impl Default for DeserializeOptions {
+    fn default() -> DeserializeOptions {
+        DeserializeOptions::new()
+    }
+}
+
+/// A summary of a serialized bitmap, returned by [`RoaringBitmap::validate_serialized`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The number of containers of the bitmap.
+    pub containers: usize,
+    /// The number of values of the bitmap.
+    pub cardinality: u64,
+    /// The number of bytes used by the serialized bitmap.
+    pub len: usize,
+}
 
 impl RoaringBitmap {
     /// Return the size in bytes of the serialized output.
@@ -30,22 +146,26 @@ impl RoaringBitmap {
     /// assert_eq!(rb1, rb2);
     /// ```
     pub fn serialized_size(&self) -> usize {
-        let container_sizes: usize = self
-            .containers
-            .iter()
-            .map(|container| match container.store {
-                Store::Array(ref values) => 8 + values.len() as usize * 2,
-                Store::Bitmap(..) => 8 + 8 * 1024,
-            })
-            .sum();
+        let size = self.containers.len();
+        let container_sizes: usize =
+            self.containers.iter().map(|container| payload_size(&container.store)).sum();
 
-        // header + container sizes
-        8 + container_sizes
+        if self.has_run_containers() {
+            // cookie + run container bitmap + descriptions + offsets + container sizes
+            let offsets = if size >= NO_OFFSET_THRESHOLD { 4 * size } else { 0 };
+            4 + (size + 7) / 8 + 4 * size + offsets + container_sizes
+        } else {
+            // cookie + size + descriptions + offsets + container sizes
+            8 + 8 * size + container_sizes
+        }
     }
 
     /// Serialize this bitmap into [the standard Roaring on-disk format][format].
     /// This is compatible with the official C/C++, Java and Go implementations.
     ///
+    /// When the bitmap contains run containers (see [`RoaringBitmap::run_optimize`])
+    /// the run-capable variant of the format is written.
+    ///
     /// [format]: https://github.com/RoaringBitmap/RoaringFormatSpec
     ///
     /// # Examples
@@ -61,24 +181,36 @@ impl RoaringBitmap {
     /// assert_eq!(rb1, rb2);
     /// ```
     pub fn serialize_into<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
-        writer.write_u32::<LittleEndian>(SERIAL_COOKIE_NO_RUNCONTAINER)?;
-        writer.write_u32::<LittleEndian>(self.containers.len() as u32)?;
+        let size = self.containers.len();
+        let has_run_containers = self.has_run_containers();
+
+        let mut offset = if has_run_containers {
+            writer.write_u32::<LittleEndian>(
+                u32::from(SERIAL_COOKIE) | ((size.saturating_sub(1) as u32) << 16),
+            )?;
+            let mut run_container_bitmap = vec![0u8; (size + 7) / 8];
+            for (i, container) in self.containers.iter().enumerate() {
+                if let Store::Run(..) = container.store {
+                    run_container_bitmap[i / 8] |= 1 << (i % 8);
+                }
+            }
+            writer.write_all(&run_container_bitmap)?;
+            4 + run_container_bitmap.len() as u32 + 8 * size as u32
+        } else {
+            writer.write_u32::<LittleEndian>(SERIAL_COOKIE_NO_RUNCONTAINER)?;
+            writer.write_u32::<LittleEndian>(size as u32)?;
+            8 + 8 * size as u32
+        };
 
         for container in &self.containers {
             writer.write_u16::<LittleEndian>(container.key)?;
             writer.write_u16::<LittleEndian>((container.len() - 1) as u16)?;
         }
 
-        let mut offset = 8 + 8 * self.containers.len() as u32;
-        for container in &self.containers {
-            writer.write_u32::<LittleEndian>(offset)?;
-            match container.store {
-                Store::Array(ref values) => {
-                    offset += values.len() as u32 * 2;
-                }
-                Store::Bitmap(..) => {
-                    offset += 8 * 1024;
-                }
+        if !has_run_containers || size >= NO_OFFSET_THRESHOLD {
+            for container in &self.containers {
+                writer.write_u32::<LittleEndian>(offset)?;
+                offset += payload_size(&container.store) as u32;
             }
         }
 
@@ -94,12 +226,172 @@ impl RoaringBitmap {
                         writer.write_u64::<LittleEndian>(value)?;
                     }
                 }
+                Store::Run(ref runs) => {
+                    writer.write_u16::<LittleEndian>(runs.run_amount() as u16)?;
+                    for interval in runs.as_slice() {
+                        writer.write_u16::<LittleEndian>(interval.start)?;
+                        writer.write_u16::<LittleEndian>(interval.length)?;
+                    }
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Return the size in bytes of the output of [`RoaringBitmap::serialize_frozen_into`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let rb: RoaringBitmap = (1..4).collect();
+    /// let mut bytes = Vec::with_capacity(rb.frozen_serialized_size());
+    /// rb.serialize_frozen_into(&mut bytes).unwrap();
+    /// assert_eq!(bytes.len(), rb.frozen_serialized_size());
+    /// ```
+    pub fn frozen_serialized_size(&self) -> usize {
+        let container_sizes: usize = self
+            .containers
+            .iter()
+            .map(|container| match container.store {
+                Store::Array(ref values) => values.len() as usize * 2,
+                Store::Bitmap(..) => 8 * 1024,
+                Store::Run(ref runs) => runs.run_amount() as usize * 4,
+            })
+            .sum();
+
+        // container sizes + keys, counts and typecodes + header
+        container_sizes + 5 * self.containers.len() + 4
+    }
+
+    /// Serialize this bitmap into the frozen format of CRoaring, as written by its
+    /// `roaring_bitmap_frozen_serialize` function.
+    ///
+    /// This format mirrors the memory layout of CRoaring and can be used in place without
+    /// being parsed, see [`RoaringBitmap::frozen_view`]. To be read by CRoaring the bytes
+    /// must be placed at an address that is 32 bytes aligned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let rb1: RoaringBitmap = (1..4).collect();
+    /// let mut bytes = vec![];
+    /// rb1.serialize_frozen_into(&mut bytes).unwrap();
+    /// let view = RoaringBitmap::frozen_view(&bytes).unwrap();
+    ///
+    /// assert_eq!(rb1, view.to_bitmap());
+    /// ```
+    pub fn serialize_frozen_into<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        // The containers are grouped by type: bitsets, then runs and then arrays
+        for container in &self.containers {
+            if let Store::Bitmap(ref bits) = container.store {
+                for &value in bits.as_array() {
+                    writer.write_u64::<LittleEndian>(value)?;
+                }
+            }
+        }
+        for container in &self.containers {
+            if let Store::Run(ref runs) = container.store {
+                for interval in runs.as_slice() {
+                    writer.write_u16::<LittleEndian>(interval.start)?;
+                    writer.write_u16::<LittleEndian>(interval.length)?;
+                }
+            }
+        }
+        for container in &self.containers {
+            if let Store::Array(ref values) = container.store {
+                for &value in values.iter() {
+                    writer.write_u16::<LittleEndian>(value)?;
+                }
+            }
+        }
+
+        for container in &self.containers {
+            writer.write_u16::<LittleEndian>(container.key)?;
+        }
+        for container in &self.containers {
+            let count = match container.store {
+                Store::Run(ref runs) => runs.run_amount(),
+                _ => container.len() - 1,
+            };
+            writer.write_u16::<LittleEndian>(count as u16)?;
+        }
+        for container in &self.containers {
+            let typecode = match container.store {
+                Store::Array(..) => ARRAY_CONTAINER_TYPE,
+                Store::Bitmap(..) => BITSET_CONTAINER_TYPE,
+                Store::Run(..) => RUN_CONTAINER_TYPE,
+            };
+            writer.write_u8(typecode)?;
+        }
+
+        let header = ((self.containers.len() as u32) << 15) | u32::from(FROZEN_COOKIE);
+        writer.write_u32::<LittleEndian>(header)
+    }
+
+    /// Creates a read-only view over a bitmap in the frozen format of CRoaring, as written
+    /// by [`RoaringBitmap::serialize_frozen_into`] or `roaring_bitmap_frozen_serialize`.
+    ///
+    /// The headers of the format and the bounds of the containers are checked, the values
+    /// of the containers are read in place. Unlike CRoaring, the bytes do not need to be
+    /// aligned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let rb: RoaringBitmap = (1..4).chain(100_000..200_000).collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_frozen_into(&mut bytes).unwrap();
+    ///
+    /// let view = RoaringBitmap::frozen_view(&bytes).unwrap();
+    /// assert!(view.contains(3));
+    /// assert_eq!(view.len(), rb.len());
+    /// assert!(RoaringBitmap::frozen_view(&bytes[1..]).is_err());
+    /// ```
+    pub fn frozen_view(bytes: &[u8]) -> Result<RoaringBitmapView<'_>, DeserializeError> {
+        RoaringBitmapView::frozen(bytes)
+    }
+
+    /// Checks that the bytes hold a valid bitmap in [the standard Roaring on-disk
+    /// format][format], without deserializing it.
+    ///
+    /// The values of the containers are checked like [`RoaringBitmap::deserialize_from`]
+    /// does, the keys and the offsets of the containers are checked too. The bitmap must
+    /// use all of the bytes, trailing bytes are reported as an error.
+    ///
+    /// [format]: https://github.com/RoaringBitmap/RoaringFormatSpec
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let rb: RoaringBitmap = (1..4).chain(100_000..200_000).collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let report = RoaringBitmap::validate_serialized(&bytes).unwrap();
+    /// assert_eq!(report.containers, 4);
+    /// assert_eq!(report.cardinality, rb.len());
+    /// assert_eq!(report.len, bytes.len());
+    ///
+    /// bytes.push(0);
+    /// assert!(RoaringBitmap::validate_serialized(&bytes).is_err());
+    /// ```
+    pub fn validate_serialized(bytes: &[u8]) -> Result<ValidationReport, DeserializeError> {
+        RoaringBitmapView::validate_portable(bytes)
+    }
+
+    fn has_run_containers(&self) -> bool {
+        self.containers.iter().any(|container| matches!(container.store, Store::Run(..)))
+    }
+
     /// Deserialize a bitmap into memory from [the standard Roaring on-disk
     /// format][format]. This is compatible with the official C/C++, Java and
     /// Go implementations. This method checks that all of the internal values
@@ -120,8 +412,46 @@ impl RoaringBitmap {
     ///
     /// assert_eq!(rb1, rb2);
     /// ```
-    pub fn deserialize_from<R: io::Read>(reader: R) -> io::Result<RoaringBitmap> {
-        RoaringBitmap::deserialize_from_impl(reader, ArrayStore::try_from, BitmapStore::try_from)
+    pub fn deserialize_from<R: io::Read>(reader: R) -> Result<RoaringBitmap, DeserializeError> {
+        RoaringBitmap::deserialize_limited_from(reader, &mut DeserializeOptions::new())
+    }
+
+    /// Deserialize a bitmap into memory from [the standard Roaring on-disk
+    /// format][format] like [`RoaringBitmap::deserialize_from`], without exceeding
+    /// the limits of the given [`DeserializeOptions`].
+    ///
+    /// [format]: https://github.com/RoaringBitmap/RoaringFormatSpec
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::{DeserializeOptions, RoaringBitmap};
+    ///
+    /// let mut rb1: RoaringBitmap = (1..1000).collect();
+    /// rb1.run_optimize();
+    /// let mut bytes = vec![];
+    /// rb1.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let options = DeserializeOptions::new().max_bytes(bytes.len());
+    /// let rb2 = RoaringBitmap::deserialize_with(&bytes[..], &options).unwrap();
+    /// assert_eq!(rb1, rb2);
+    ///
+    /// let options = DeserializeOptions::new().allow_runs(false);
+    /// assert!(RoaringBitmap::deserialize_with(&bytes[..], &options).is_err());
+    /// ```
+    pub fn deserialize_with<R: io::Read>(
+        reader: R,
+        options: &DeserializeOptions,
+    ) -> Result<RoaringBitmap, DeserializeError> {
+        RoaringBitmap::deserialize_limited_from(reader, &mut options.clone())
+    }
+
+    /// Deserializes a checked bitmap, consuming the limits of the options.
+    pub(crate) fn deserialize_limited_from<R: io::Read>(
+        reader: R,
+        options: &mut DeserializeOptions,
+    ) -> Result<RoaringBitmap, DeserializeError> {
+        RoaringBitmap::deserialize_from_impl(reader, options, check_array, check_bitmap, check_runs)
     }
 
     /// Deserialize a bitmap into memory from [the standard Roaring on-disk
@@ -143,83 +473,303 @@ impl RoaringBitmap {
     ///
     /// assert_eq!(rb1, rb2);
     /// ```
-    pub fn deserialize_unchecked_from<R: io::Read>(reader: R) -> io::Result<RoaringBitmap> {
-        RoaringBitmap::deserialize_from_impl::<R, _, Infallible, _, Infallible>(
+    pub fn deserialize_unchecked_from<R: io::Read>(
+        reader: R,
+    ) -> Result<RoaringBitmap, DeserializeError> {
+        RoaringBitmap::deserialize_from_impl(
             reader,
-            |values| Ok(ArrayStore::from_vec_unchecked(values)),
-            |len, values| Ok(BitmapStore::from_unchecked(len, values)),
+            &mut DeserializeOptions::new(),
+            |_, values| Ok(ArrayStore::from_vec_unchecked(values)),
+            |_, len, values| Ok(BitmapStore::from_unchecked(len, values)),
+            |_, _, intervals| Ok(RunStore::from_vec_unchecked(intervals)),
         )
     }
 
-    fn deserialize_from_impl<R, A, AErr, B, BErr>(
+    /// Deserialize the values of a bitmap that are within a range from [the standard
+    /// Roaring on-disk format][format], like [`RoaringBitmap::deserialize_from`] does.
+    ///
+    /// Only the headers and the containers overlapping the range are read: the reader
+    /// seeks to the containers using the offsets stored in the headers. The offsets are
+    /// relative to the position of the reader when this method is called. Once done, the
+    /// reader is left at an unspecified position.
+    ///
+    /// [format]: https://github.com/RoaringBitmap/RoaringFormatSpec
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    /// use std::io::Cursor;
+    ///
+    /// let rb: RoaringBitmap = (1..4).chain(100_000..200_000).collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let partial = RoaringBitmap::deserialize_range_from(Cursor::new(&bytes), 2..100_010).unwrap();
+    /// assert_eq!(partial, (2..4).chain(100_000..100_010).collect());
+    /// ```
+    pub fn deserialize_range_from<R, B>(
+        mut reader: R,
+        range: B,
+    ) -> Result<RoaringBitmap, DeserializeError>
+    where
+        R: io::Read + io::Seek,
+        B: RangeBounds<u32>,
+    {
+        let start = reader.stream_position()?;
+        let options = &mut DeserializeOptions::new();
+        let header = read_header(&mut reader, options)?;
+
+        let range = match util::convert_range_to_inclusive(range) {
+            Some(range) => range,
+            None => return Ok(RoaringBitmap::new()),
+        };
+        let (start_key, end_key) = (util::split(*range.start()).0, util::split(*range.end()).0);
+
+        let mut containers = Vec::new();
+        for i in 0..header.len() {
+            let key = header.key(i);
+            if key > end_key {
+                break;
+            }
+            match header.offset(i) {
+                Some(_) if key < start_key => continue,
+                Some(offset) => {
+                    reader.seek(io::SeekFrom::Start(start + u64::from(offset)))?;
+                }
+                // Without offsets the containers are read one after the other
+                None => (),
+            }
+            let store = read_store(
+                &mut reader,
+                options,
+                key,
+                header.cardinality(i),
+                header.is_run(i),
+                &check_array,
+                &check_bitmap,
+                &check_runs,
+            )?;
+            if key >= start_key {
+                containers.push(Container { key, store });
+            }
+        }
+
+        let mut bitmap = RoaringBitmap { containers };
+        bitmap.remove_range(..range.start());
+        bitmap.remove_range((Bound::Excluded(range.end()), Bound::Unbounded));
+        Ok(bitmap)
+    }
+
+    fn deserialize_from_impl<R, A, B, C>(
         mut reader: R,
+        options: &mut DeserializeOptions,
         a: A,
         b: B,
-    ) -> io::Result<RoaringBitmap>
+        c: C,
+    ) -> Result<RoaringBitmap, DeserializeError>
     where
         R: io::Read,
-        A: Fn(Vec<u16>) -> Result<ArrayStore, AErr>,
-        AErr: Error + Send + Sync + 'static,
-        B: Fn(u64, Box<[u64; 1024]>) -> Result<BitmapStore, BErr>,
-        BErr: Error + Send + Sync + 'static,
+        A: Fn(u16, Vec<u16>) -> Result<ArrayStore, DeserializeError>,
+        B: Fn(u16, u64, Box<[u64; 1024]>) -> Result<BitmapStore, DeserializeError>,
+        C: Fn(u16, u64, Vec<Interval>) -> Result<RunStore, DeserializeError>,
     {
-        let (size, has_offsets) = {
-            let cookie = reader.read_u32::<LittleEndian>()?;
-            if cookie == SERIAL_COOKIE_NO_RUNCONTAINER {
-                (reader.read_u32::<LittleEndian>()? as usize, true)
-            } else if (cookie as u16) == SERIAL_COOKIE {
-                return Err(io::Error::new(io::ErrorKind::Other, "run containers are unsupported"));
-            } else {
-                return Err(io::Error::new(io::ErrorKind::Other, "unknown cookie value"));
-            }
-        };
+        let header = read_header(&mut reader, options)?;
+
+        let mut containers = Vec::with_capacity(header.len());
 
-        if size > u16::MAX as usize + 1 {
-            return Err(io::Error::new(io::ErrorKind::Other, "size is greater than supported"));
+        // Read each container
+        for i in 0..header.len() {
+            let key = header.key(i);
+            let len = header.cardinality(i);
+            let store = read_store(&mut reader, options, key, len, header.is_run(i), &a, &b, &c)?;
+            containers.push(Container { key, store });
         }
 
-        let mut description_bytes = vec![0u8; size * 4];
-        reader.read_exact(&mut description_bytes)?;
-        let mut description_bytes = &description_bytes[..];
+        Ok(RoaringBitmap { containers })
+    }
+}
+
+/// The headers of a bitmap in the portable format, that describe its containers.
+struct Header {
+    run_container_bitmap: Option<Vec<u8>>,
+    descriptions: Vec<u8>,
+    offsets: Option<Vec<u8>>,
+}
+
+impl Header {
+    fn len(&self) -> usize {
+        self.descriptions.len() / 4
+    }
+
+    fn key(&self, i: usize) -> u16 {
+        u16::from_le_bytes([self.descriptions[i * 4], self.descriptions[i * 4 + 1]])
+    }
+
+    fn cardinality(&self, i: usize) -> u64 {
+        u64::from(u16::from_le_bytes([self.descriptions[i * 4 + 2], self.descriptions[i * 4 + 3]]))
+            + 1
+    }
+
+    fn is_run(&self, i: usize) -> bool {
+        self.run_container_bitmap.as_ref().map_or(false, |bm| bm[i / 8] & (1 << (i % 8)) != 0)
+    }
+
+    fn offset(&self, i: usize) -> Option<u32> {
+        let offsets = self.offsets.as_ref()?;
+        Some(u32::from_le_bytes([
+            offsets[i * 4],
+            offsets[i * 4 + 1],
+            offsets[i * 4 + 2],
+            offsets[i * 4 + 3],
+        ]))
+    }
+}
 
-        if has_offsets {
-            let mut offsets = vec![0u8; size * 4];
-            reader.read_exact(&mut offsets)?;
-            drop(offsets); // Not useful when deserializing into memory
+fn read_header<R: io::Read>(
+    reader: &mut R,
+    options: &mut DeserializeOptions,
+) -> Result<Header, DeserializeError> {
+    // First read the cookie to determine which version of the format we are reading
+    let (size, has_offsets, has_run_containers) = {
+        let cookie = reader.read_u32::<LittleEndian>()?;
+        if cookie == SERIAL_COOKIE_NO_RUNCONTAINER {
+            options.consume_bytes(8)?;
+            (reader.read_u32::<LittleEndian>()? as usize, true, false)
+        } else if (cookie as u16) == SERIAL_COOKIE {
+            options.consume_bytes(4)?;
+            let size = ((cookie >> 16) + 1) as usize;
+            (size, size >= NO_OFFSET_THRESHOLD, true)
+        } else {
+            return Err(DeserializeError::UnknownCookie(cookie));
         }
+    };
 
-        let mut containers = Vec::with_capacity(size);
+    if size > u16::MAX as usize + 1 {
+        return Err(DeserializeError::TooManyContainers);
+    }
+    options.consume_containers(size)?;
 
-        for _ in 0..size {
-            let key = description_bytes.read_u16::<LittleEndian>()?;
-            let len = u64::from(description_bytes.read_u16::<LittleEndian>()?) + 1;
+    // Read the run container bitmap if necessary
+    let run_container_bitmap = if has_run_containers {
+        options.consume_bytes((size + 7) / 8)?;
+        let mut bitmap = vec![0u8; (size + 7) / 8];
+        reader.read_exact(&mut bitmap)?;
+        if !options.allow_runs && bitmap.iter().any(|&flags| flags != 0) {
+            return Err(DeserializeError::RunContainersNotAllowed);
+        }
+        Some(bitmap)
+    } else {
+        None
+    };
 
-            let store = if len <= 4096 {
-                let mut values = vec![0; len as usize];
-                reader.read_exact(cast_slice_mut(&mut values))?;
-                values.iter_mut().for_each(|n| *n = u16::from_le(*n));
-                let array = a(values).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-                Store::Array(array)
-            } else {
-                let mut values = Box::new([0; 1024]);
-                reader.read_exact(cast_slice_mut(&mut values[..]))?;
-                values.iter_mut().for_each(|n| *n = u64::from_le(*n));
-                let bitmap =
-                    b(len, values).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-                Store::Bitmap(bitmap)
-            };
+    // Read the container descriptions
+    options.consume_bytes(if has_offsets { size * 8 } else { size * 4 })?;
+    let mut descriptions = vec![0u8; size * 4];
+    reader.read_exact(&mut descriptions)?;
 
-            containers.push(Container { key, store });
+    // The containers must be ordered by strictly increasing keys
+    let mut keys = descriptions.chunks_exact(4).map(|d| u16::from_le_bytes([d[0], d[1]]));
+    if let Some(mut previous) = keys.next() {
+        for key in keys {
+            if key <= previous {
+                return Err(DeserializeError::UnsortedKeys { key });
+            }
+            previous = key;
         }
+    }
 
-        Ok(RoaringBitmap { containers })
+    let cardinality: u64 = descriptions
+        .chunks_exact(4)
+        .map(|description| u64::from(u16::from_le_bytes([description[2], description[3]])) + 1)
+        .sum();
+    options.consume_cardinality(cardinality)?;
+
+    let offsets = if has_offsets {
+        let mut offsets = vec![0u8; size * 4];
+        reader.read_exact(&mut offsets)?;
+        Some(offsets)
+    } else {
+        None
+    };
+
+    Ok(Header { run_container_bitmap, descriptions, offsets })
+}
+
+/// Reads the values of the container at the position of the reader.
+#[allow(clippy::too_many_arguments)]
+fn read_store<R, A, B, C>(
+    reader: &mut R,
+    options: &mut DeserializeOptions,
+    key: u16,
+    len: u64,
+    is_run_container: bool,
+    a: &A,
+    b: &B,
+    c: &C,
+) -> Result<Store, DeserializeError>
+where
+    R: io::Read,
+    A: Fn(u16, Vec<u16>) -> Result<ArrayStore, DeserializeError>,
+    B: Fn(u16, u64, Box<[u64; 1024]>) -> Result<BitmapStore, DeserializeError>,
+    C: Fn(u16, u64, Vec<Interval>) -> Result<RunStore, DeserializeError>,
+{
+    if is_run_container {
+        options.consume_bytes(2)?;
+        let runs = reader.read_u16::<LittleEndian>()?;
+        options.consume_bytes(runs as usize * 4)?;
+        let mut values = vec![0u16; runs as usize * 2];
+        reader.read_exact(cast_slice_mut(&mut values))?;
+        let intervals = values
+            .chunks_exact(2)
+            .map(|pair| Interval { start: u16::from_le(pair[0]), length: u16::from_le(pair[1]) })
+            .collect();
+        Ok(Store::Run(c(key, len, intervals)?))
+    } else if len <= 4096 {
+        options.consume_bytes(len as usize * 2)?;
+        let mut values = vec![0; len as usize];
+        reader.read_exact(cast_slice_mut(&mut values))?;
+        values.iter_mut().for_each(|n| *n = u16::from_le(*n));
+        Ok(Store::Array(a(key, values)?))
+    } else {
+        options.consume_bytes(8 * 1024)?;
+        let mut values = Box::new([0; 1024]);
+        reader.read_exact(cast_slice_mut(&mut values[..]))?;
+        values.iter_mut().for_each(|n| *n = u64::from_le(*n));
+        Ok(Store::Bitmap(b(key, len, values)?))
+    }
+}
+
+fn check_array(key: u16, values: Vec<u16>) -> Result<ArrayStore, DeserializeError> {
+    ArrayStore::try_from(values).map_err(|_| DeserializeError::UnsortedArray { key })
+}
+
+fn check_bitmap(
+    key: u16,
+    len: u64,
+    values: Box<[u64; 1024]>,
+) -> Result<BitmapStore, DeserializeError> {
+    BitmapStore::try_from(len, values).map_err(|e| e.into_deserialize_error(key))
+}
+
+fn check_runs(key: u16, len: u64, intervals: Vec<Interval>) -> Result<RunStore, DeserializeError> {
+    RunStore::try_from_len(len, intervals).map_err(|e| e.into_deserialize_error(key))
+}
+
+/// The number of bytes used to serialize the values of a container.
+pub(crate) fn payload_size(store: &Store) -> usize {
+    match store {
+        Store::Array(values) => values.len() as usize * 2,
+        Store::Bitmap(..) => 8 * 1024,
+        Store::Run(runs) => 2 + runs.run_amount() as usize * 4,
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::RoaringBitmap;
+    use crate::{DeserializeError, DeserializeOptions, RoaringBitmap, ValidationReport};
     use proptest::prelude::*;
+    use std::io;
 
     proptest! {
         #[test]
@@ -230,5 +780,231 @@ mod test {
             bitmap.serialize_into(&mut buffer).unwrap();
             prop_assert_eq!(bitmap, RoaringBitmap::deserialize_from(buffer.as_slice()).unwrap());
         }
+
+        #[test]
+        fn test_deserialize_range_from(
+            mut bitmap in RoaringBitmap::arbitrary(),
+            run_optimize in any::<bool>(),
+            start in any::<u32>(),
+            end in any::<u32>(),
+        ) {
+            if run_optimize {
+                bitmap.run_optimize();
+            }
+            let (start, end) = (start.min(end), start.max(end));
+            let mut buffer = vec![0; 3];
+            bitmap.serialize_into(&mut buffer).unwrap();
+            let mut reader = io::Cursor::new(&buffer);
+            reader.set_position(3);
+
+            let partial = RoaringBitmap::deserialize_range_from(reader, start..=end).unwrap();
+            let expected: RoaringBitmap = bitmap.iter().filter(|v| (start..=end).contains(v)).collect();
+            prop_assert_eq!(partial, expected);
+        }
+
+        #[test]
+        fn test_validate_serialized(
+            mut bitmap in RoaringBitmap::arbitrary(),
+            run_optimize in any::<bool>(),
+        ) {
+            if run_optimize {
+                bitmap.run_optimize();
+            }
+            let mut buffer = Vec::new();
+            bitmap.serialize_into(&mut buffer).unwrap();
+            let report = RoaringBitmap::validate_serialized(&buffer).unwrap();
+            prop_assert_eq!(report.containers, bitmap.containers.len());
+            prop_assert_eq!(report.cardinality, bitmap.len());
+            prop_assert_eq!(report.len, buffer.len());
+        }
+
+        #[test]
+        fn test_serialization_with_runs(
+            mut bitmap in RoaringBitmap::arbitrary(),
+        ) {
+            bitmap.run_optimize();
+            let mut buffer = Vec::new();
+            bitmap.serialize_into(&mut buffer).unwrap();
+            prop_assert_eq!(buffer.len(), bitmap.serialized_size());
+            prop_assert_eq!(bitmap, RoaringBitmap::deserialize_from(buffer.as_slice()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_deserialize_runs_without_offsets() {
+        // Less than NO_OFFSET_THRESHOLD containers, the offset header is omitted
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            0x3B, 0x30, 0x01, 0x00, // cookie 12347 and two containers
+            0b0000_0010,            // the second container is a run container
+            0x00, 0x00, 0x01, 0x00, // key 0, two values
+            0x02, 0x00, 0x0B, 0x00, // key 2, twelve values
+            0x05, 0x00, 0x07, 0x00, // array [5, 7]
+            0x02, 0x00,             // two runs
+            0x00, 0x00, 0x01, 0x00, // 0..=1
+            0x0A, 0x00, 0x09, 0x00, // 10..=19
+        ];
+        let expected: RoaringBitmap =
+            [5, 7].into_iter().chain((0..2).chain(10..20).map(|v| (2 << 16) + v)).collect();
+        assert_eq!(RoaringBitmap::deserialize_from(bytes).unwrap(), expected);
+
+        // The header cardinality does not match the runs
+        let mut invalid = bytes.to_vec();
+        invalid[11] = 0x0C;
+        assert!(matches!(
+            RoaringBitmap::deserialize_from(&invalid[..]),
+            Err(DeserializeError::CardinalityMismatch { key: 2, expected: 13, actual: 12 })
+        ));
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            0x3A, 0x30, 0x00, 0x00, // cookie 12346
+            0x01, 0x00, 0x00, 0x00, // one container
+            0x04, 0x00, 0x01, 0x00, // key 4, two values
+            0x10, 0x00, 0x00, 0x00, // offset 16
+            0x07, 0x00, 0x05, 0x00, // array [7, 5]
+        ];
+        assert!(matches!(
+            RoaringBitmap::deserialize_from(bytes),
+            Err(DeserializeError::UnsortedArray { key: 4 })
+        ));
+        assert!(matches!(
+            RoaringBitmap::validate_serialized(bytes),
+            Err(DeserializeError::UnsortedArray { key: 4 })
+        ));
+
+        assert!(matches!(
+            RoaringBitmap::deserialize_from(&bytes[..18]),
+            Err(DeserializeError::Io(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+
+        let mut invalid = bytes.to_vec();
+        invalid[0] = 0x00;
+        assert!(matches!(
+            RoaringBitmap::deserialize_from(&invalid[..]),
+            Err(DeserializeError::UnknownCookie(0x3000))
+        ));
+
+        let mut invalid = bytes.to_vec();
+        invalid[6] = 0x01;
+        assert!(matches!(
+            RoaringBitmap::deserialize_from(&invalid[..]),
+            Err(DeserializeError::TooManyContainers)
+        ));
+
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            0x3A, 0x30, 0x00, 0x00, // cookie 12346
+            0x02, 0x00, 0x00, 0x00, // two containers
+            0x04, 0x00, 0x00, 0x00, // key 4, one value
+            0x04, 0x00, 0x00, 0x00, // key 4, one value
+            0x18, 0x00, 0x00, 0x00, // offset 24
+            0x1A, 0x00, 0x00, 0x00, // offset 26
+            0x05, 0x00,             // array [5]
+            0x07, 0x00,             // array [7]
+        ];
+        assert!(matches!(
+            RoaringBitmap::deserialize_from(bytes),
+            Err(DeserializeError::UnsortedKeys { key: 4 })
+        ));
+        assert!(matches!(
+            RoaringBitmap::deserialize_with(bytes, &DeserializeOptions::default()),
+            Err(DeserializeError::UnsortedKeys { key: 4 })
+        ));
+        assert!(matches!(
+            RoaringBitmap::validate_serialized(bytes),
+            Err(DeserializeError::UnsortedKeys { key: 4 })
+        ));
+    }
+
+    #[test]
+    fn test_validate_serialized_layout() {
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            0x3A, 0x30, 0x00, 0x00, // cookie 12346
+            0x01, 0x00, 0x00, 0x00, // one container
+            0x04, 0x00, 0x01, 0x00, // key 4, two values
+            0x10, 0x00, 0x00, 0x00, // offset 16
+            0x05, 0x00, 0x07, 0x00, // array [5, 7]
+        ];
+        let report = RoaringBitmap::validate_serialized(bytes).unwrap();
+        assert_eq!(report, ValidationReport { containers: 1, cardinality: 2, len: 20 });
+
+        let mut invalid = bytes.to_vec();
+        invalid[12] = 0x0E;
+        assert!(matches!(
+            RoaringBitmap::validate_serialized(&invalid),
+            Err(DeserializeError::InvalidOffset { key: 4 })
+        ));
+
+        let mut invalid = bytes.to_vec();
+        invalid.extend_from_slice(&[0x00, 0x00]);
+        assert!(matches!(
+            RoaringBitmap::validate_serialized(&invalid),
+            Err(DeserializeError::LengthMismatch { expected: 20, actual: 22 })
+        ));
+    }
+
+    #[test]
+    fn test_deserialize_with_limits() {
+        let bitmap: RoaringBitmap = (0..10).chain(1 << 16..(1 << 16) + 5000).collect();
+        let mut bytes = Vec::new();
+        bitmap.serialize_into(&mut bytes).unwrap();
+
+        let options = DeserializeOptions::new()
+            .max_containers(2)
+            .max_bytes(bytes.len())
+            .max_cardinality(5010)
+            .allow_runs(false);
+        assert_eq!(RoaringBitmap::deserialize_with(&bytes[..], &options).unwrap(), bitmap);
+
+        let limited = |options: DeserializeOptions| {
+            RoaringBitmap::deserialize_with(&bytes[..], &options).unwrap_err()
+        };
+        assert!(matches!(
+            limited(options.clone().max_containers(1)),
+            DeserializeError::ContainerLimitExceeded
+        ));
+        assert!(matches!(
+            limited(options.clone().max_bytes(bytes.len() - 1)),
+            DeserializeError::ByteLimitExceeded
+        ));
+        assert!(matches!(
+            limited(options.clone().max_cardinality(5009)),
+            DeserializeError::CardinalityLimitExceeded
+        ));
+
+        // The header of a bitmap with the maximum amount of bitmap containers,
+        // the limits are exceeded before anything is allocated
+        #[rustfmt::skip]
+        let hostile: &[u8] = &[
+            0x3A, 0x30, 0x00, 0x00, // cookie 12346
+            0x00, 0x00, 0x01, 0x00, // 65536 containers
+        ];
+        assert!(matches!(
+            RoaringBitmap::deserialize_with(hostile, &DeserializeOptions::new().max_bytes(1024)),
+            Err(DeserializeError::ByteLimitExceeded)
+        ));
+        assert!(matches!(
+            RoaringBitmap::deserialize_with(hostile, &options),
+            Err(DeserializeError::ContainerLimitExceeded)
+        ));
+    }
+
+    #[test]
+    fn test_deserialize_with_runs_not_allowed() {
+        let mut bitmap: RoaringBitmap = (0..10).chain(1 << 16..(1 << 16) + 5000).collect();
+        bitmap.run_optimize();
+        let mut bytes = Vec::new();
+        bitmap.serialize_into(&mut bytes).unwrap();
+
+        let options = DeserializeOptions::new().allow_runs(false);
+        assert!(matches!(
+            RoaringBitmap::deserialize_with(&bytes[..], &options),
+            Err(DeserializeError::RunContainersNotAllowed)
+        ));
     }
 }
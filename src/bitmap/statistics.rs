@@ -0,0 +1,98 @@
+use crate::RoaringBitmap;
+
+use super::serialization::payload_size;
+use super::store::Store;
+
+/// Detailed statistics about the containers of a [`RoaringBitmap`],
+/// returned by [`RoaringBitmap::statistics`].
+///
+/// The counters follow the `roaring_bitmap_statistics` of CRoaring.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// The number of containers of the bitmap.
+    pub n_containers: u64,
+    /// The number of array containers of the bitmap.
+    pub n_array_containers: u64,
+    /// The number of bitset containers of the bitmap.
+    pub n_bitset_containers: u64,
+    /// The number of run containers of the bitmap.
+    pub n_run_containers: u64,
+    /// The number of values stored in array containers.
+    pub n_values_array_containers: u64,
+    /// The number of values stored in bitset containers.
+    pub n_values_bitset_containers: u64,
+    /// The number of values stored in run containers.
+    pub n_values_run_containers: u64,
+    /// The number of bytes used to serialize the values of the array containers.
+    pub n_bytes_array_containers: u64,
+    /// The number of bytes used to serialize the values of the bitset containers.
+    pub n_bytes_bitset_containers: u64,
+    /// The number of bytes used to serialize the values of the run containers.
+    pub n_bytes_run_containers: u64,
+    /// The number of bytes allocated on the heap, as returned by [`RoaringBitmap::memory_usage`].
+    pub n_heap_bytes: u64,
+    /// The number of values of the bitmap.
+    pub cardinality: u64,
+    /// The smallest value of the bitmap.
+    pub min_value: Option<u32>,
+    /// The greatest value of the bitmap.
+    pub max_value: Option<u32>,
+}
+
+impl RoaringBitmap {
+    /// Returns detailed statistics about the containers of this bitmap:
+    /// how many of each kind there are, the values and bytes they hold
+    /// and the memory allocated by the bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringBitmap;
+    ///
+    /// let mut rb: RoaringBitmap = (0..10).collect();
+    /// rb.insert_range(65_536..196_608);
+    ///
+    /// let stats = rb.statistics();
+    /// assert_eq!(stats.n_containers, 3);
+    /// assert_eq!(stats.n_array_containers, 1);
+    /// assert_eq!(stats.n_run_containers, 2);
+    /// assert_eq!(stats.n_values_array_containers, 10);
+    /// assert_eq!(stats.n_bytes_array_containers, 20);
+    /// assert_eq!(stats.cardinality, 131_082);
+    /// assert_eq!(stats.max_value, Some(196_607));
+    /// ```
+    pub fn statistics(&self) -> Statistics {
+        let mut stats = Statistics {
+            n_containers: self.containers.len() as u64,
+            n_heap_bytes: self.memory_usage() as u64,
+            min_value: self.min(),
+            max_value: self.max(),
+            ..Statistics::default()
+        };
+
+        for container in &self.containers {
+            let len = container.len();
+            let bytes = payload_size(&container.store) as u64;
+            match container.store {
+                Store::Array(_) => {
+                    stats.n_array_containers += 1;
+                    stats.n_values_array_containers += len;
+                    stats.n_bytes_array_containers += bytes;
+                }
+                Store::Bitmap(_) => {
+                    stats.n_bitset_containers += 1;
+                    stats.n_values_bitset_containers += len;
+                    stats.n_bytes_bitset_containers += bytes;
+                }
+                Store::Run(_) => {
+                    stats.n_run_containers += 1;
+                    stats.n_values_run_containers += len;
+                    stats.n_bytes_run_containers += bytes;
+                }
+            }
+            stats.cardinality += len;
+        }
+
+        stats
+    }
+}
@@ -7,9 +7,11 @@ use std::cmp::Ordering;
 use std::cmp::Ordering::*;
 use std::convert::{TryFrom, TryInto};
 use std::fmt::{Display, Formatter};
+use std::mem;
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitXor, RangeInclusive, Sub, SubAssign};
 
 use super::bitmap_store::{bit, key, BitmapStore, BITMAP_LENGTH};
+use super::run_store::{Interval, RunStore};
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct ArrayStore {
@@ -178,10 +180,40 @@ impl ArrayStore {
         BitmapStore::from_unchecked(len, bits)
     }
 
+    pub fn to_run_store(&self) -> RunStore {
+        let mut intervals: Vec<Interval> = Vec::new();
+        for &index in self.iter() {
+            match intervals.last_mut() {
+                Some(last) if u32::from(last.end()) + 1 == u32::from(index) => last.length += 1,
+                _ => intervals.push(Interval::new(index, index)),
+            }
+        }
+        RunStore::from_vec_unchecked(intervals)
+    }
+
+    /// Returns the number of runs of consecutive values.
+    pub fn run_amount(&self) -> u64 {
+        let breaks = self.vec.windows(2).filter(|w| u32::from(w[0]) + 1 != u32::from(w[1])).count();
+        if self.vec.is_empty() {
+            0
+        } else {
+            breaks as u64 + 1
+        }
+    }
+
     pub fn len(&self) -> u64 {
         self.vec.len() as u64
     }
 
+    /// Returns the number of bytes allocated on the heap, including the spare capacity.
+    pub fn heap_size(&self) -> usize {
+        self.vec.capacity() * mem::size_of::<u16>()
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.vec.shrink_to_fit();
+    }
+
     pub fn min(&self) -> Option<u16> {
         self.vec.first().copied()
     }
@@ -190,6 +222,36 @@ impl ArrayStore {
         self.vec.last().copied()
     }
 
+    pub fn next_value(&self, index: u16) -> Option<u16> {
+        self.vec.get(self.vec.partition_point(|&value| value < index)).cloned()
+    }
+
+    pub fn previous_value(&self, index: u16) -> Option<u16> {
+        let i = self.vec.partition_point(|&value| value <= index);
+        i.checked_sub(1).map(|i| self.vec[i])
+    }
+
+    pub fn next_absent(&self, index: u16) -> Option<u16> {
+        let i = self.vec.partition_point(|&value| value < index);
+        let present = self.vec[i..]
+            .iter()
+            .zip(u32::from(index)..)
+            .take_while(|&(&value, expected)| u32::from(value) == expected)
+            .count();
+        u16::try_from(u32::from(index) + present as u32).ok()
+    }
+
+    pub fn previous_absent(&self, index: u16) -> Option<u16> {
+        let i = self.vec.partition_point(|&value| value <= index);
+        let present = self.vec[..i]
+            .iter()
+            .rev()
+            .zip((0..=u32::from(index)).rev())
+            .take_while(|&(&value, expected)| u32::from(value) == expected)
+            .count();
+        u32::from(index).checked_sub(present as u32).map(|previous| previous as u16)
+    }
+
     pub fn rank(&self, index: u16) -> u64 {
         match self.vec.binary_search(&index) {
             Ok(i) => i as u64 + 1,
@@ -201,7 +263,7 @@ impl ArrayStore {
         self.vec.get(n as usize).cloned()
     }
 
-    pub fn iter(&self) -> std::slice::Iter<u16> {
+    pub fn iter(&self) -> std::slice::Iter<'_, u16> {
         self.vec.iter()
     }
 
@@ -213,6 +275,22 @@ impl ArrayStore {
         &self.vec
     }
 
+    /// Adds `offset` to the values, returns the values that do not overflow
+    /// and the values that overflow, wrapped around.
+    pub fn add_offset(&self, offset: u16) -> (ArrayStore, ArrayStore) {
+        let split = self.vec.partition_point(|&value| value <= u16::MAX - offset);
+        let shift =
+            |values: &[u16]| values.iter().map(|value| value.wrapping_add(offset)).collect();
+        (
+            ArrayStore { vec: shift(&self.vec[..split]) },
+            ArrayStore { vec: shift(&self.vec[split..]) },
+        )
+    }
+
+    pub fn ranges(&self) -> ArrayRanges<'_> {
+        ArrayRanges { values: &self.vec }
+    }
+
     /// Retains only the elements specified by the predicate.
     pub fn retain(&mut self, mut f: impl FnMut(u16) -> bool) {
         // Idea to avoid branching from "Engineering Fast Indexes for Big Data
@@ -231,6 +309,44 @@ impl ArrayStore {
     }
 }
 
+/// An iterator over the maximal runs of consecutive values of an array.
+pub struct ArrayRanges<'a> {
+    values: &'a [u16],
+}
+
+impl Iterator for ArrayRanges<'_> {
+    type Item = RangeInclusive<u16>;
+
+    fn next(&mut self) -> Option<RangeInclusive<u16>> {
+        let first = *self.values.first()?;
+        let len = self
+            .values
+            .iter()
+            .zip(u32::from(first)..)
+            .take_while(|&(&value, expected)| u32::from(value) == expected)
+            .count();
+        let (run, rest) = self.values.split_at(len);
+        self.values = rest;
+        Some(first..=run[len - 1])
+    }
+}
+
+impl DoubleEndedIterator for ArrayRanges<'_> {
+    fn next_back(&mut self) -> Option<RangeInclusive<u16>> {
+        let last = *self.values.last()?;
+        let len = self
+            .values
+            .iter()
+            .rev()
+            .zip((0..=u32::from(last)).rev())
+            .take_while(|&(&value, expected)| u32::from(value) == expected)
+            .count();
+        let (rest, run) = self.values.split_at(self.values.len() - len);
+        self.values = rest;
+        Some(run[0]..=last)
+    }
+}
+
 impl Default for ArrayStore {
     fn default() -> Self {
         ArrayStore::new()
@@ -338,6 +454,16 @@ impl BitAndAssign<&BitmapStore> for ArrayStore {
     }
 }
 
+impl BitAndAssign<&RunStore> for ArrayStore {
+    fn bitand_assign(&mut self, rhs: &RunStore) {
+        let mut intervals = rhs.as_slice().iter().peekable();
+        self.retain(|x| {
+            while intervals.next_if(|iv| iv.end() < x).is_some() {}
+            intervals.peek().map_or(false, |iv| iv.start <= x)
+        });
+    }
+}
+
 impl Sub<Self> for &ArrayStore {
     type Output = ArrayStore;
 
@@ -377,6 +503,16 @@ impl SubAssign<&BitmapStore> for ArrayStore {
     }
 }
 
+impl SubAssign<&RunStore> for ArrayStore {
+    fn sub_assign(&mut self, rhs: &RunStore) {
+        let mut intervals = rhs.as_slice().iter().peekable();
+        self.retain(|x| {
+            while intervals.next_if(|iv| iv.end() < x).is_some() {}
+            intervals.peek().map_or(true, |iv| iv.start > x)
+        });
+    }
+}
+
 impl BitXor<Self> for &ArrayStore {
     type Output = ArrayStore;
 
@@ -401,6 +537,7 @@ mod tests {
         match s {
             Store::Array(vec) => vec.vec,
             Store::Bitmap(bits) => bits.to_array_store().vec,
+            Store::Run(runs) => runs.to_array_store().vec,
         }
     }
 
@@ -408,6 +545,7 @@ mod tests {
         match s {
             Store::Array(vec) => Store::Bitmap(vec.to_bitmap_store()),
             Store::Bitmap(..) => s,
+            Store::Run(runs) => Store::Bitmap(runs.to_bitmap_store()),
         }
     }
 
@@ -1,9 +1,11 @@
 use std::borrow::Borrow;
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
+use std::mem;
 use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign, RangeInclusive, SubAssign};
 
-use super::ArrayStore;
+use super::{ArrayStore, Interval, RunStore};
+use crate::DeserializeError;
 
 pub const BITMAP_LENGTH: usize = 1024;
 
@@ -178,6 +180,44 @@ impl BitmapStore {
         removed
     }
 
+    pub fn flip_range(&mut self, range: RangeInclusive<u16>) {
+        let start = *range.start();
+        let end = *range.end();
+
+        let (start_key, start_bit) = (key(start), bit(start));
+        let (end_key, end_bit) = (key(end), bit(end));
+
+        let mut existed = 0;
+        for i in start_key..=end_key {
+            let mut mask = u64::MAX;
+            if i == start_key {
+                mask &= u64::MAX << start_bit;
+            }
+            if i == end_key {
+                mask &= u64::MAX >> (63 - end_bit);
+            }
+            existed += u64::from((self.bits[i] & mask).count_ones());
+            self.bits[i] ^= mask;
+        }
+
+        self.len = self.len + u64::from(end - start) + 1 - 2 * existed;
+    }
+
+    /// Retains only the values specified by the predicate, a word at a time.
+    pub fn retain(&mut self, mut f: impl FnMut(u16) -> bool) {
+        for (key, word) in self.bits.iter_mut().enumerate() {
+            let mut remaining = *word;
+            while remaining != 0 {
+                let bit = remaining.trailing_zeros();
+                remaining &= remaining - 1;
+                if !f((key * 64) as u16 + bit as u16) {
+                    *word &= !(1 << bit);
+                    self.len -= 1;
+                }
+            }
+        }
+    }
+
     pub fn contains(&self, index: u16) -> bool {
         self.bits[key(index)] & (1 << bit(index)) != 0
     }
@@ -221,6 +261,48 @@ impl BitmapStore {
         self.bits.iter().zip(other.bits.iter()).all(|(&i1, &i2)| (i1 & i2) == i1)
     }
 
+    pub fn to_run_store(&self) -> RunStore {
+        let mut intervals = Vec::new();
+        let mut i = 0;
+        let mut word = self.bits[0];
+        loop {
+            while word == 0 {
+                i += 1;
+                if i == BITMAP_LENGTH {
+                    return RunStore::from_vec_unchecked(intervals);
+                }
+                word = self.bits[i];
+            }
+            let start = (i * 64) as u32 + word.trailing_zeros();
+
+            // Fill the zeros below the lowest set bit and look for the end of the run
+            let mut filled = word | (word - 1);
+            while filled == u64::MAX {
+                i += 1;
+                if i == BITMAP_LENGTH {
+                    intervals.push(Interval::new(start as u16, u16::MAX));
+                    return RunStore::from_vec_unchecked(intervals);
+                }
+                filled = self.bits[i];
+            }
+            let end = (i * 64) as u32 + filled.trailing_ones() - 1;
+            intervals.push(Interval::new(start as u16, end as u16));
+
+            // Clear the trailing ones that we just consumed
+            word = filled & (filled + 1);
+        }
+    }
+
+    /// Returns the number of runs of consecutive values.
+    pub fn run_amount(&self) -> u64 {
+        let mut runs = 0;
+        for (i, &word) in self.bits.iter().enumerate() {
+            let next = self.bits.get(i + 1).copied().unwrap_or(0);
+            runs += u64::from((!word & (word << 1)).count_ones()) + ((word >> 63) & !next);
+        }
+        runs
+    }
+
     pub fn to_array_store(&self) -> ArrayStore {
         let mut vec = Vec::with_capacity(self.len as usize);
         for (index, mut bit) in self.bits.iter().cloned().enumerate() {
@@ -236,6 +318,11 @@ impl BitmapStore {
         self.len
     }
 
+    /// Returns the number of bytes allocated on the heap by the boxed bits.
+    pub fn heap_size(&self) -> usize {
+        mem::size_of::<[u64; BITMAP_LENGTH]>()
+    }
+
     pub fn min(&self) -> Option<u16> {
         self.bits
             .iter()
@@ -253,6 +340,26 @@ impl BitmapStore {
             .map(|(index, bit)| (index * 64 + (63 - bit.leading_zeros() as usize)) as u16)
     }
 
+    pub fn next_value(&self, index: u16) -> Option<u16> {
+        let next = find_forward(&self.bits, index as usize, BITMAP_LENGTH * 64, true);
+        (next < BITMAP_LENGTH * 64).then(|| next as u16)
+    }
+
+    pub fn previous_value(&self, index: u16) -> Option<u16> {
+        let previous = find_backward(&self.bits, 0, index as usize + 1, true);
+        (previous > 0).then(|| (previous - 1) as u16)
+    }
+
+    pub fn next_absent(&self, index: u16) -> Option<u16> {
+        let next = find_forward(&self.bits, index as usize, BITMAP_LENGTH * 64, false);
+        (next < BITMAP_LENGTH * 64).then(|| next as u16)
+    }
+
+    pub fn previous_absent(&self, index: u16) -> Option<u16> {
+        let previous = find_backward(&self.bits, 0, index as usize + 1, false);
+        (previous > 0).then(|| (previous - 1) as u16)
+    }
+
     pub fn rank(&self, index: u16) -> u64 {
         let (key, bit) = (key(index), bit(index));
 
@@ -291,6 +398,65 @@ impl BitmapStore {
             .sum::<u64>()
     }
 
+    pub fn intersection_len_run(&self, other: &RunStore) -> u64 {
+        other.as_slice().iter().map(|iv| self.range_len(iv.start..=iv.end())).sum()
+    }
+
+    /// Returns the number of bits set in the range.
+    fn range_len(&self, range: RangeInclusive<u16>) -> u64 {
+        let (start_key, start_bit) = (key(*range.start()), bit(*range.start()));
+        let (end_key, end_bit) = (key(*range.end()), bit(*range.end()));
+
+        if start_key == end_key {
+            let mask = (u64::MAX << start_bit) & (u64::MAX >> (63 - end_bit));
+            return u64::from((self.bits[start_key] & mask).count_ones());
+        }
+
+        let first = (self.bits[start_key] & (u64::MAX << start_bit)).count_ones();
+        let middle: u32 = self.bits[start_key + 1..end_key].iter().map(|w| w.count_ones()).sum();
+        let last = (self.bits[end_key] & (u64::MAX >> (63 - end_bit))).count_ones();
+        u64::from(first + middle + last)
+    }
+
+    /// Unions the bits of `other` without maintaining the cardinality,
+    /// `repair_len` must be called before the length is read again.
+    pub fn lazy_or_bitmap(&mut self, other: &BitmapStore) {
+        for (index1, &index2) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *index1 |= index2;
+        }
+    }
+
+    /// Unions the values of `other` without maintaining the cardinality.
+    pub fn lazy_or_array(&mut self, other: &ArrayStore) {
+        for &index in other.iter() {
+            self.bits[key(index)] |= 1 << bit(index);
+        }
+    }
+
+    /// Unions the intervals of `other` without maintaining the cardinality.
+    pub fn lazy_or_run(&mut self, other: &RunStore) {
+        for iv in other.as_slice() {
+            let (start_key, start_bit) = (key(iv.start), bit(iv.start));
+            let (end_key, end_bit) = (key(iv.end()), bit(iv.end()));
+
+            if start_key == end_key {
+                self.bits[start_key] |= (u64::MAX << start_bit) & (u64::MAX >> (63 - end_bit));
+                continue;
+            }
+
+            self.bits[start_key] |= u64::MAX << start_bit;
+            for word in &mut self.bits[start_key + 1..end_key] {
+                *word = u64::MAX;
+            }
+            self.bits[end_key] |= u64::MAX >> (63 - end_bit);
+        }
+    }
+
+    /// Recomputes the cardinality after a series of lazy operations.
+    pub fn repair_len(&mut self) {
+        self.len = self.bits.iter().map(|word| u64::from(word.count_ones())).sum();
+    }
+
     pub fn iter(&self) -> BitmapIter<&[u64; BITMAP_LENGTH]> {
         BitmapIter::new(&self.bits)
     }
@@ -302,6 +468,34 @@ impl BitmapStore {
     pub fn as_array(&self) -> &[u64; BITMAP_LENGTH] {
         &self.bits
     }
+
+    /// Adds `offset` to the values, returns the values that do not overflow
+    /// and the values that overflow, wrapped around.
+    pub fn add_offset(&self, offset: u16) -> (BitmapStore, BitmapStore) {
+        let mut low = BitmapStore::new();
+        let mut high = BitmapStore::new();
+        let (words, bits) = (offset as usize / 64, offset as usize % 64);
+        let mut put = |index: usize, word: u64| {
+            if index < BITMAP_LENGTH {
+                low.bits[index] |= word;
+            } else {
+                high.bits[index - BITMAP_LENGTH] |= word;
+            }
+        };
+        for (index, &word) in self.bits.iter().enumerate() {
+            put(index + words, word << bits);
+            if bits != 0 {
+                put(index + words + 1, word >> (64 - bits));
+            }
+        }
+        low.len = low.bits.iter().map(|word| u64::from(word.count_ones())).sum();
+        high.len = self.len - low.len;
+        (low, high)
+    }
+
+    pub fn ranges(&self) -> BitmapRanges<'_> {
+        BitmapRanges { bits: &self.bits, front: 0, back: BITMAP_LENGTH * 64 }
+    }
 }
 
 // this can be done in 3 instructions on x86-64 with bmi2 with: tzcnt(pdep(1 << rank, value))
@@ -330,6 +524,16 @@ pub enum ErrorKind {
     Cardinality { expected: u64, actual: u64 },
 }
 
+impl Error {
+    pub(crate) fn into_deserialize_error(self, key: u16) -> DeserializeError {
+        match self.kind {
+            ErrorKind::Cardinality { expected, actual } => {
+                DeserializeError::CardinalityMismatch { key, expected, actual }
+            }
+        }
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self.kind {
@@ -408,6 +612,162 @@ impl<B: Borrow<[u64; BITMAP_LENGTH]>> DoubleEndedIterator for BitmapIter<B> {
     }
 }
 
+impl<B: Borrow<[u64; BITMAP_LENGTH]>> BitmapIter<B> {
+    /// Writes the next values, combined with `high`, into `buf` a word at a time,
+    /// returns the number of written values.
+    pub fn next_many(&mut self, high: u32, buf: &mut [u32]) -> usize {
+        let mut n = 0;
+        while n < buf.len() {
+            if self.value == 0 {
+                self.key += 1;
+                self.value = match self.key.cmp(&self.key_back) {
+                    Ordering::Less => self.bits.borrow()[self.key],
+                    Ordering::Equal => self.value_back,
+                    Ordering::Greater => break,
+                };
+                continue;
+            }
+            let base = high | (64 * self.key) as u32;
+            for slot in &mut buf[n..] {
+                if self.value == 0 {
+                    break;
+                }
+                *slot = base | self.value.trailing_zeros();
+                self.value &= self.value - 1;
+                n += 1;
+            }
+        }
+        n
+    }
+
+    /// Skips the values lower than `index`, returns the number of skipped values.
+    pub fn advance_to(&mut self, index: u16) -> u64 {
+        let (word, bit) = (key(index), bit(index));
+        if word < self.key {
+            return 0;
+        }
+        let mut skipped = 0;
+        if word > self.key {
+            if self.key < self.key_back {
+                skipped += u64::from(self.value.count_ones());
+                let end = word.min(self.key_back);
+                let bits = &self.bits.borrow()[self.key + 1..end];
+                skipped += bits.iter().map(|w| u64::from(w.count_ones())).sum::<u64>();
+                self.key = end;
+                self.value =
+                    if end == self.key_back { self.value_back } else { self.bits.borrow()[end] };
+            }
+            if word > self.key {
+                skipped += u64::from(self.value.count_ones());
+                self.value = 0;
+                return skipped;
+            }
+        }
+        let mask = (1 << bit) - 1;
+        skipped += u64::from((self.value & mask).count_ones());
+        self.value &= !mask;
+        skipped
+    }
+
+    /// Skips the values greater than `index` from the back, returns the number of skipped values.
+    pub fn advance_back_to(&mut self, index: u16) -> u64 {
+        let (word, bit) = (key(index), bit(index));
+        if word > self.key_back {
+            return 0;
+        }
+        let mut skipped = 0;
+        if word < self.key_back {
+            if self.key < self.key_back {
+                skipped += u64::from(self.value_back.count_ones());
+                let start = word.max(self.key);
+                let bits = &self.bits.borrow()[start + 1..self.key_back];
+                skipped += bits.iter().map(|w| u64::from(w.count_ones())).sum::<u64>();
+                self.key_back = start;
+                if start > self.key {
+                    self.value_back = self.bits.borrow()[start];
+                }
+            }
+            if word < self.key_back {
+                let value =
+                    if self.key_back <= self.key { &mut self.value } else { &mut self.value_back };
+                skipped += u64::from(value.count_ones());
+                *value = 0;
+                return skipped;
+            }
+        }
+        let value = if self.key_back <= self.key { &mut self.value } else { &mut self.value_back };
+        let mask = if bit == 63 { u64::MAX } else { (1 << (bit + 1)) - 1 };
+        skipped += u64::from((*value & !mask).count_ones());
+        *value &= mask;
+        skipped
+    }
+}
+
+/// An iterator over the maximal runs of consecutive values of a bitmap,
+/// the words are scanned with `trailing_zeros` and `leading_zeros`.
+pub struct BitmapRanges<'a> {
+    bits: &'a [u64; BITMAP_LENGTH],
+    // The bits in `front..back` are not iterated yet
+    front: usize,
+    back: usize,
+}
+
+impl Iterator for BitmapRanges<'_> {
+    type Item = RangeInclusive<u16>;
+
+    fn next(&mut self) -> Option<RangeInclusive<u16>> {
+        let start = find_forward(self.bits, self.front, self.back, true);
+        if start == self.back {
+            self.front = self.back;
+            return None;
+        }
+        let end = find_forward(self.bits, start, self.back, false);
+        self.front = end;
+        Some(start as u16..=(end - 1) as u16)
+    }
+}
+
+impl DoubleEndedIterator for BitmapRanges<'_> {
+    fn next_back(&mut self) -> Option<RangeInclusive<u16>> {
+        let end = find_backward(self.bits, self.front, self.back, true);
+        if end == self.front {
+            self.back = self.front;
+            return None;
+        }
+        let start = find_backward(self.bits, self.front, end, false);
+        self.back = start;
+        Some(start as u16..=(end - 1) as u16)
+    }
+}
+
+/// Returns the index of the first bit in `from..to` that is equal to `set`, or `to`.
+fn find_forward(bits: &[u64; BITMAP_LENGTH], from: usize, to: usize, set: bool) -> usize {
+    let flip = if set { 0 } else { u64::MAX };
+    let mut index = from;
+    while index < to {
+        let word = (bits[index / 64] ^ flip) >> (index % 64);
+        if word != 0 {
+            return (index + word.trailing_zeros() as usize).min(to);
+        }
+        index = (index / 64 + 1) * 64;
+    }
+    to
+}
+
+/// Returns the index following the last bit in `from..to` that is equal to `set`, or `from`.
+fn find_backward(bits: &[u64; BITMAP_LENGTH], from: usize, to: usize, set: bool) -> usize {
+    let flip = if set { 0 } else { u64::MAX };
+    let mut index = to;
+    while index > from {
+        let word = (bits[(index - 1) / 64] ^ flip) << (63 - (index - 1) % 64);
+        if word != 0 {
+            return (index - word.leading_zeros() as usize).max(from);
+        }
+        index = (index - 1) / 64 * 64;
+    }
+    from
+}
+
 #[inline]
 pub fn key(index: u16) -> usize {
     index as usize / 64
@@ -445,12 +805,36 @@ impl BitOrAssign<&ArrayStore> for BitmapStore {
     }
 }
 
+impl BitOrAssign<&RunStore> for BitmapStore {
+    fn bitor_assign(&mut self, rhs: &RunStore) {
+        for iv in rhs.as_slice() {
+            self.insert_range(iv.start..=iv.end());
+        }
+    }
+}
+
 impl BitAndAssign<&Self> for BitmapStore {
     fn bitand_assign(&mut self, rhs: &Self) {
         op_bitmaps(self, rhs, BitAndAssign::bitand_assign);
     }
 }
 
+impl BitAndAssign<&RunStore> for BitmapStore {
+    fn bitand_assign(&mut self, rhs: &RunStore) {
+        // Remove the gaps between the intervals
+        let mut start = 0u32;
+        for iv in rhs.as_slice() {
+            if u32::from(iv.start) > start {
+                self.remove_range(start as u16..=iv.start - 1);
+            }
+            start = u32::from(iv.end()) + 1;
+        }
+        if start <= u32::from(u16::MAX) {
+            self.remove_range(start as u16..=u16::MAX);
+        }
+    }
+}
+
 impl SubAssign<&Self> for BitmapStore {
     #[allow(clippy::suspicious_op_assign_impl)]
     fn sub_assign(&mut self, rhs: &Self) {
@@ -471,6 +855,14 @@ impl SubAssign<&ArrayStore> for BitmapStore {
     }
 }
 
+impl SubAssign<&RunStore> for BitmapStore {
+    fn sub_assign(&mut self, rhs: &RunStore) {
+        for iv in rhs.as_slice() {
+            self.remove_range(iv.start..=iv.end());
+        }
+    }
+}
+
 impl BitXorAssign<&Self> for BitmapStore {
     fn bitxor_assign(&mut self, rhs: &Self) {
         op_bitmaps(self, rhs, BitXorAssign::bitxor_assign);
@@ -490,3 +882,11 @@ impl BitXorAssign<&ArrayStore> for BitmapStore {
         self.len = len as u64;
     }
 }
+
+impl BitXorAssign<&RunStore> for BitmapStore {
+    fn bitxor_assign(&mut self, rhs: &RunStore) {
+        for iv in rhs.as_slice() {
+            self.flip_range(iv.start..=iv.end());
+        }
+    }
+}
@@ -1,5 +1,6 @@
 mod array_store;
 mod bitmap_store;
+mod run_store;
 
 use std::mem;
 use std::ops::{
@@ -8,15 +9,17 @@ use std::ops::{
 use std::{slice, vec};
 
 use self::bitmap_store::BITMAP_LENGTH;
-use self::Store::{Array, Bitmap};
+use self::Store::{Array, Bitmap, Run};
 
-pub use self::array_store::ArrayStore;
-pub use self::bitmap_store::{BitmapIter, BitmapStore};
+pub use self::array_store::{ArrayRanges, ArrayStore};
+pub use self::bitmap_store::{BitmapIter, BitmapRanges, BitmapStore};
+pub use self::run_store::{Interval, RunIter, RunStore};
 
 #[derive(Clone)]
 pub enum Store {
     Array(ArrayStore),
     Bitmap(BitmapStore),
+    Run(RunStore),
 }
 
 pub enum Iter<'a> {
@@ -24,6 +27,15 @@ pub enum Iter<'a> {
     Vec(vec::IntoIter<u16>),
     BitmapBorrowed(BitmapIter<&'a [u64; BITMAP_LENGTH]>),
     BitmapOwned(BitmapIter<Box<[u64; BITMAP_LENGTH]>>),
+    RunBorrowed(RunIter<&'a [Interval]>),
+    RunOwned(RunIter<Vec<Interval>>),
+}
+
+/// An iterator over the maximal runs of consecutive values of a store.
+pub enum Ranges<'a> {
+    Array(ArrayRanges<'a>),
+    Bitmap(BitmapRanges<'a>),
+    Run(slice::Iter<'a, Interval>),
 }
 
 impl Store {
@@ -32,13 +44,14 @@ impl Store {
     }
 
     pub fn full() -> Store {
-        Store::Bitmap(BitmapStore::full())
+        Store::Run(RunStore::full())
     }
 
     pub fn insert(&mut self, index: u16) -> bool {
         match self {
             Array(vec) => vec.insert(index),
             Bitmap(bits) => bits.insert(index),
+            Run(runs) => runs.insert(index),
         }
     }
 
@@ -51,6 +64,7 @@ impl Store {
         match self {
             Array(vec) => vec.insert_range(range),
             Bitmap(bits) => bits.insert_range(range),
+            Run(runs) => runs.insert_range(range),
         }
     }
 
@@ -61,6 +75,7 @@ impl Store {
         match self {
             Array(vec) => vec.push(index),
             Bitmap(bits) => bits.push(index),
+            Run(runs) => runs.push(index),
         }
     }
 
@@ -75,6 +90,7 @@ impl Store {
         match self {
             Array(vec) => vec.push_unchecked(index),
             Bitmap(bits) => bits.push_unchecked(index),
+            Run(runs) => runs.push_unchecked(index),
         }
     }
 
@@ -82,6 +98,7 @@ impl Store {
         match self {
             Array(vec) => vec.remove(index),
             Bitmap(bits) => bits.remove(index),
+            Run(runs) => runs.remove(index),
         }
     }
 
@@ -93,6 +110,26 @@ impl Store {
         match self {
             Array(vec) => vec.remove_range(range),
             Bitmap(bits) => bits.remove_range(range),
+            Run(runs) => runs.remove_range(range),
+        }
+    }
+
+    pub fn flip_range(&mut self, range: RangeInclusive<u16>) {
+        match self {
+            Bitmap(bits) => bits.flip_range(range),
+            this => {
+                let interval = Interval::new(*range.start(), *range.end());
+                let range = Run(RunStore::from_vec_unchecked(vec![interval]));
+                *this = BitXor::bitxor(&*this, &range);
+            }
+        }
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(u16) -> bool) {
+        match self {
+            Array(vec) => vec.retain(f),
+            Bitmap(bits) => bits.retain(f),
+            Run(runs) => runs.retain(f),
         }
     }
 
@@ -100,6 +137,7 @@ impl Store {
         match self {
             Array(vec) => vec.contains(index),
             Bitmap(bits) => bits.contains(index),
+            Run(runs) => runs.contains(index),
         }
     }
 
@@ -107,6 +145,7 @@ impl Store {
         match self {
             Array(vec) => vec.contains_range(range),
             Bitmap(bits) => bits.contains_range(range),
+            Run(runs) => runs.contains_range(range),
         }
     }
 
@@ -121,6 +160,11 @@ impl Store {
             (Array(vec), Bitmap(bits)) | (Bitmap(bits), Array(vec)) => {
                 vec.iter().all(|&i| !bits.contains(i))
             }
+            (Run(runs1), Run(runs2)) => runs1.is_disjoint(runs2),
+            (Run(runs), Array(vec)) | (Array(vec), Run(runs)) => runs.is_disjoint_array(vec),
+            (Run(runs), Bitmap(bits)) | (Bitmap(bits), Run(runs)) => {
+                bits.intersection_len_run(runs) == 0
+            }
         }
     }
 
@@ -129,7 +173,14 @@ impl Store {
             (Array(vec1), Array(vec2)) => vec1.is_subset(vec2),
             (Bitmap(bits1), Bitmap(bits2)) => bits1.is_subset(bits2),
             (Array(vec), Bitmap(bits)) => vec.iter().all(|&i| bits.contains(i)),
-            (Bitmap(..), &Array(..)) => false,
+            (Bitmap(..), Array(..)) => false,
+            (Run(runs1), Run(runs2)) => runs1.is_subset(runs2),
+            (Run(runs), Array(vec)) => runs.is_subset_array(vec),
+            (Run(runs), Bitmap(bits)) => {
+                runs.as_slice().iter().all(|iv| bits.contains_range(iv.start..=iv.end()))
+            }
+            (Array(vec), Run(runs)) => runs.intersection_len_array(vec) == vec.len(),
+            (Bitmap(bits), Run(runs)) => bits.intersection_len_run(runs) == bits.len(),
         }
     }
 
@@ -139,6 +190,11 @@ impl Store {
             (Bitmap(bits1), Bitmap(bits2)) => bits1.intersection_len_bitmap(bits2),
             (Array(vec), Bitmap(bits)) => bits.intersection_len_array(vec),
             (Bitmap(bits), Array(vec)) => bits.intersection_len_array(vec),
+            (Run(runs1), Run(runs2)) => runs1.intersection_len(runs2),
+            (Run(runs), Array(vec)) | (Array(vec), Run(runs)) => runs.intersection_len_array(vec),
+            (Run(runs), Bitmap(bits)) | (Bitmap(bits), Run(runs)) => {
+                bits.intersection_len_run(runs)
+            }
         }
     }
 
@@ -146,6 +202,23 @@ impl Store {
         match self {
             Array(vec) => vec.len(),
             Bitmap(bits) => bits.len(),
+            Run(runs) => runs.len(),
+        }
+    }
+
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Array(vec) => vec.heap_size(),
+            Bitmap(bits) => bits.heap_size(),
+            Run(runs) => runs.heap_size(),
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            Array(vec) => vec.shrink_to_fit(),
+            Bitmap(..) => (),
+            Run(runs) => runs.shrink_to_fit(),
         }
     }
 
@@ -153,6 +226,7 @@ impl Store {
         match self {
             Array(vec) => vec.min(),
             Bitmap(bits) => bits.min(),
+            Run(runs) => runs.min(),
         }
     }
 
@@ -160,6 +234,39 @@ impl Store {
         match self {
             Array(vec) => vec.max(),
             Bitmap(bits) => bits.max(),
+            Run(runs) => runs.max(),
+        }
+    }
+
+    pub fn next_value(&self, index: u16) -> Option<u16> {
+        match self {
+            Array(vec) => vec.next_value(index),
+            Bitmap(bits) => bits.next_value(index),
+            Run(runs) => runs.next_value(index),
+        }
+    }
+
+    pub fn previous_value(&self, index: u16) -> Option<u16> {
+        match self {
+            Array(vec) => vec.previous_value(index),
+            Bitmap(bits) => bits.previous_value(index),
+            Run(runs) => runs.previous_value(index),
+        }
+    }
+
+    pub fn next_absent(&self, index: u16) -> Option<u16> {
+        match self {
+            Array(vec) => vec.next_absent(index),
+            Bitmap(bits) => bits.next_absent(index),
+            Run(runs) => runs.next_absent(index),
+        }
+    }
+
+    pub fn previous_absent(&self, index: u16) -> Option<u16> {
+        match self {
+            Array(vec) => vec.previous_absent(index),
+            Bitmap(bits) => bits.previous_absent(index),
+            Run(runs) => runs.previous_absent(index),
         }
     }
 
@@ -167,6 +274,7 @@ impl Store {
         match self {
             Array(vec) => vec.rank(index),
             Bitmap(bits) => bits.rank(index),
+            Run(runs) => runs.rank(index),
         }
     }
 
@@ -174,6 +282,34 @@ impl Store {
         match self {
             Array(vec) => vec.select(n),
             Bitmap(bits) => bits.select(n),
+            Run(runs) => runs.select(n),
+        }
+    }
+
+    /// Adds `offset` to the values, returns the values that do not overflow
+    /// and the values that overflow, wrapped around.
+    pub fn add_offset(&self, offset: u16) -> (Store, Store) {
+        match self {
+            Array(vec) => {
+                let (low, high) = vec.add_offset(offset);
+                (Array(low), Array(high))
+            }
+            Bitmap(bits) => {
+                let (low, high) = bits.add_offset(offset);
+                (Bitmap(low), Bitmap(high))
+            }
+            Run(runs) => {
+                let (low, high) = runs.add_offset(offset);
+                (Run(low), Run(high))
+            }
+        }
+    }
+
+    pub fn ranges(&self) -> Ranges<'_> {
+        match self {
+            Array(vec) => Ranges::Array(vec.ranges()),
+            Bitmap(bits) => Ranges::Bitmap(bits.ranges()),
+            Run(runs) => Ranges::Run(runs.as_slice().iter()),
         }
     }
 
@@ -181,6 +317,25 @@ impl Store {
         match self {
             Array(arr) => Bitmap(arr.to_bitmap_store()),
             Bitmap(_) => self.clone(),
+            Run(runs) => Bitmap(runs.to_bitmap_store()),
+        }
+    }
+
+    /// Unions `rhs` into a bitmap store without maintaining its cardinality,
+    /// the other kinds of stores are unioned eagerly.
+    pub(crate) fn lazy_or(&mut self, rhs: &Store) {
+        match (self, rhs) {
+            (Bitmap(bits1), Array(vec2)) => bits1.lazy_or_array(vec2),
+            (Bitmap(bits1), Bitmap(bits2)) => bits1.lazy_or_bitmap(bits2),
+            (Bitmap(bits1), Run(runs2)) => bits1.lazy_or_run(runs2),
+            (this, rhs) => BitOrAssign::bitor_assign(this, rhs),
+        }
+    }
+
+    /// Recomputes the cardinality of a store that went through `lazy_or`.
+    pub(crate) fn repair_len(&mut self) {
+        if let Bitmap(bits) = self {
+            bits.repair_len();
         }
     }
 }
@@ -196,22 +351,29 @@ impl BitOr<&Store> for &Store {
 
     fn bitor(self, rhs: &Store) -> Store {
         match (self, rhs) {
-            (&Array(ref vec1), &Array(ref vec2)) => Array(BitOr::bitor(vec1, vec2)),
-            (&Bitmap(..), &Array(..)) => {
+            (Array(vec1), Array(vec2)) => Array(BitOr::bitor(vec1, vec2)),
+            (Bitmap(..), Array(..)) => {
                 let mut lhs = self.clone();
                 BitOrAssign::bitor_assign(&mut lhs, rhs);
                 lhs
             }
-            (&Bitmap(..), &Bitmap(..)) => {
+            (Bitmap(..), Bitmap(..)) => {
                 let mut lhs = self.clone();
                 BitOrAssign::bitor_assign(&mut lhs, rhs);
                 lhs
             }
-            (&Array(..), &Bitmap(..)) => {
+            (Array(..), Bitmap(..)) => {
                 let mut rhs = rhs.clone();
                 BitOrAssign::bitor_assign(&mut rhs, self);
                 rhs
             }
+            (Run(runs1), Run(runs2)) => Run(BitOr::bitor(runs1, runs2)),
+            (Run(runs), Array(vec)) | (Array(vec), Run(runs)) => Run(BitOr::bitor(runs, vec)),
+            (Run(runs), Bitmap(bits)) | (Bitmap(bits), Run(runs)) => {
+                let mut bits = bits.clone();
+                BitOrAssign::bitor_assign(&mut bits, runs);
+                Bitmap(bits)
+            }
         }
     }
 }
@@ -219,19 +381,25 @@ impl BitOr<&Store> for &Store {
 impl BitOrAssign<Store> for Store {
     fn bitor_assign(&mut self, mut rhs: Store) {
         match (self, &mut rhs) {
-            (&mut Array(ref mut vec1), &mut Array(ref vec2)) => {
-                *vec1 = BitOr::bitor(&*vec1, vec2);
+            (Array(vec1), Array(vec2)) => {
+                *vec1 = BitOr::bitor(&*vec1, &*vec2);
             }
-            (&mut Bitmap(ref mut bits1), &mut Array(ref vec2)) => {
-                BitOrAssign::bitor_assign(bits1, vec2);
+            (Bitmap(bits1), Array(vec2)) => {
+                BitOrAssign::bitor_assign(bits1, &*vec2);
             }
-            (&mut Bitmap(ref mut bits1), &mut Bitmap(ref bits2)) => {
-                BitOrAssign::bitor_assign(bits1, bits2);
+            (Bitmap(bits1), Bitmap(bits2)) => {
+                BitOrAssign::bitor_assign(bits1, &*bits2);
             }
-            (this @ &mut Array(..), &mut Bitmap(..)) => {
+            (Bitmap(bits1), Run(runs2)) => {
+                BitOrAssign::bitor_assign(bits1, &*runs2);
+            }
+            (this @ Array(..), Bitmap(..)) | (this @ Run(..), Bitmap(..)) => {
                 mem::swap(this, &mut rhs);
                 BitOrAssign::bitor_assign(this, rhs);
             }
+            (this @ Run(..), _) | (this @ Array(..), Run(..)) => {
+                *this = BitOr::bitor(&*this, &rhs);
+            }
         }
     }
 }
@@ -239,21 +407,27 @@ impl BitOrAssign<Store> for Store {
 impl BitOrAssign<&Store> for Store {
     fn bitor_assign(&mut self, rhs: &Store) {
         match (self, rhs) {
-            (&mut Array(ref mut vec1), &Array(ref vec2)) => {
+            (Array(vec1), Array(vec2)) => {
                 let this = mem::take(vec1);
                 *vec1 = BitOr::bitor(&this, vec2);
             }
-            (&mut Bitmap(ref mut bits1), &Array(ref vec2)) => {
+            (Bitmap(bits1), Array(vec2)) => {
                 BitOrAssign::bitor_assign(bits1, vec2);
             }
-            (&mut Bitmap(ref mut bits1), &Bitmap(ref bits2)) => {
+            (Bitmap(bits1), Bitmap(bits2)) => {
                 BitOrAssign::bitor_assign(bits1, bits2);
             }
-            (this @ &mut Array(..), &Bitmap(ref bits2)) => {
+            (Bitmap(bits1), Run(runs2)) => {
+                BitOrAssign::bitor_assign(bits1, runs2);
+            }
+            (this @ Array(..), Bitmap(bits2)) | (this @ Run(..), Bitmap(bits2)) => {
                 let mut lhs: Store = Bitmap(bits2.clone());
                 BitOrAssign::bitor_assign(&mut lhs, &*this);
                 *this = lhs;
             }
+            (this @ Run(..), _) | (this @ Array(..), Run(..)) => {
+                *this = BitOr::bitor(&*this, rhs);
+            }
         }
     }
 }
@@ -263,8 +437,13 @@ impl BitAnd<&Store> for &Store {
 
     fn bitand(self, rhs: &Store) -> Store {
         match (self, rhs) {
-            (&Array(ref vec1), &Array(ref vec2)) => Array(BitAnd::bitand(vec1, vec2)),
-            (&Bitmap(..), &Array(..)) => {
+            (Array(vec1), Array(vec2)) => Array(BitAnd::bitand(vec1, vec2)),
+            (Run(runs), Array(vec)) | (Array(vec), Run(runs)) => {
+                let mut vec = vec.clone();
+                BitAndAssign::bitand_assign(&mut vec, runs);
+                Array(vec)
+            }
+            (Bitmap(..), Array(..)) | (Run(..), Bitmap(..)) => {
                 let mut rhs = rhs.clone();
                 BitAndAssign::bitand_assign(&mut rhs, self);
                 rhs
@@ -282,19 +461,28 @@ impl BitAndAssign<Store> for Store {
     #[allow(clippy::suspicious_op_assign_impl)]
     fn bitand_assign(&mut self, mut rhs: Store) {
         match (self, &mut rhs) {
-            (&mut Array(ref mut vec1), &mut Array(ref mut vec2)) => {
+            (Array(vec1), Array(vec2)) => {
                 if vec2.len() < vec1.len() {
                     mem::swap(vec1, vec2);
                 }
                 BitAndAssign::bitand_assign(vec1, &*vec2);
             }
-            (&mut Bitmap(ref mut bits1), &mut Bitmap(ref bits2)) => {
-                BitAndAssign::bitand_assign(bits1, bits2);
+            (Bitmap(bits1), Bitmap(bits2)) => {
+                BitAndAssign::bitand_assign(bits1, &*bits2);
             }
-            (&mut Array(ref mut vec1), &mut Bitmap(ref bits2)) => {
-                BitAndAssign::bitand_assign(vec1, bits2);
+            (Array(vec1), Bitmap(bits2)) => {
+                BitAndAssign::bitand_assign(vec1, &*bits2);
+            }
+            (Array(vec1), Run(runs2)) => {
+                BitAndAssign::bitand_assign(vec1, &*runs2);
+            }
+            (Bitmap(bits1), Run(runs2)) => {
+                BitAndAssign::bitand_assign(bits1, &*runs2);
             }
-            (this @ &mut Bitmap(..), &mut Array(..)) => {
+            (Run(runs1), Run(runs2)) => {
+                *runs1 = BitAnd::bitand(&*runs1, &*runs2);
+            }
+            (this @ Bitmap(..), Array(..)) | (this @ Run(..), _) => {
                 mem::swap(this, &mut rhs);
                 BitAndAssign::bitand_assign(this, rhs);
             }
@@ -306,7 +494,7 @@ impl BitAndAssign<&Store> for Store {
     #[allow(clippy::suspicious_op_assign_impl)]
     fn bitand_assign(&mut self, rhs: &Store) {
         match (self, rhs) {
-            (&mut Array(ref mut vec1), &Array(ref vec2)) => {
+            (Array(vec1), Array(vec2)) => {
                 let (mut lhs, rhs) = if vec2.len() < vec1.len() {
                     (vec2.clone(), &*vec1)
                 } else {
@@ -316,13 +504,22 @@ impl BitAndAssign<&Store> for Store {
                 BitAndAssign::bitand_assign(&mut lhs, rhs);
                 *vec1 = lhs;
             }
-            (&mut Bitmap(ref mut bits1), &Bitmap(ref bits2)) => {
+            (Bitmap(bits1), Bitmap(bits2)) => {
                 BitAndAssign::bitand_assign(bits1, bits2);
             }
-            (&mut Array(ref mut vec1), &Bitmap(ref bits2)) => {
+            (Array(vec1), Bitmap(bits2)) => {
                 BitAndAssign::bitand_assign(vec1, bits2);
             }
-            (this @ &mut Bitmap(..), &Array(..)) => {
+            (Array(vec1), Run(runs2)) => {
+                BitAndAssign::bitand_assign(vec1, runs2);
+            }
+            (Bitmap(bits1), Run(runs2)) => {
+                BitAndAssign::bitand_assign(bits1, runs2);
+            }
+            (Run(runs1), Run(runs2)) => {
+                *runs1 = BitAnd::bitand(&*runs1, runs2);
+            }
+            (this @ Bitmap(..), Array(..)) | (this @ Run(..), _) => {
                 let mut new = rhs.clone();
                 BitAndAssign::bitand_assign(&mut new, &*this);
                 *this = new;
@@ -336,7 +533,9 @@ impl Sub<&Store> for &Store {
 
     fn sub(self, rhs: &Store) -> Store {
         match (self, rhs) {
-            (&Array(ref vec1), &Array(ref vec2)) => Array(Sub::sub(vec1, vec2)),
+            (Array(vec1), Array(vec2)) => Array(Sub::sub(vec1, vec2)),
+            (Run(runs1), Run(runs2)) => Run(Sub::sub(runs1, runs2)),
+            (Run(runs), Array(vec)) => Run(Sub::sub(runs, vec)),
             _ => {
                 let mut lhs = self.clone();
                 SubAssign::sub_assign(&mut lhs, rhs);
@@ -349,18 +548,37 @@ impl Sub<&Store> for &Store {
 impl SubAssign<&Store> for Store {
     fn sub_assign(&mut self, rhs: &Store) {
         match (self, rhs) {
-            (&mut Array(ref mut vec1), &Array(ref vec2)) => {
+            (Array(vec1), Array(vec2)) => {
                 SubAssign::sub_assign(vec1, vec2);
             }
-            (&mut Bitmap(ref mut bits1), &Array(ref vec2)) => {
+            (Bitmap(bits1), Array(vec2)) => {
                 SubAssign::sub_assign(bits1, vec2);
             }
-            (&mut Bitmap(ref mut bits1), &Bitmap(ref bits2)) => {
+            (Bitmap(bits1), Bitmap(bits2)) => {
                 SubAssign::sub_assign(bits1, bits2);
             }
-            (&mut Array(ref mut vec1), &Bitmap(ref bits2)) => {
+            (Array(vec1), Bitmap(bits2)) => {
                 SubAssign::sub_assign(vec1, bits2);
             }
+            (Array(vec1), Run(runs2)) => {
+                SubAssign::sub_assign(vec1, runs2);
+            }
+            (Bitmap(bits1), Run(runs2)) => {
+                SubAssign::sub_assign(bits1, runs2);
+            }
+            (Run(runs1), Run(runs2)) => {
+                *runs1 = Sub::sub(&*runs1, runs2);
+            }
+            (Run(runs1), Array(vec2)) => {
+                *runs1 = Sub::sub(&*runs1, vec2);
+            }
+            (this @ Run(..), Bitmap(bits2)) => {
+                let mut lhs = this.to_bitmap();
+                if let Bitmap(bits1) = &mut lhs {
+                    SubAssign::sub_assign(bits1, bits2);
+                }
+                *this = lhs;
+            }
         }
     }
 }
@@ -370,12 +588,19 @@ impl BitXor<&Store> for &Store {
 
     fn bitxor(self, rhs: &Store) -> Store {
         match (self, rhs) {
-            (&Array(ref vec1), &Array(ref vec2)) => Array(BitXor::bitxor(vec1, vec2)),
-            (&Array(..), &Bitmap(..)) => {
+            (Array(vec1), Array(vec2)) => Array(BitXor::bitxor(vec1, vec2)),
+            (Array(..), Bitmap(..)) => {
                 let mut lhs = rhs.clone();
                 BitXorAssign::bitxor_assign(&mut lhs, self);
                 lhs
             }
+            (Run(runs1), Run(runs2)) => Run(BitXor::bitxor(runs1, runs2)),
+            (Run(runs), Array(vec)) | (Array(vec), Run(runs)) => Run(BitXor::bitxor(runs, vec)),
+            (Run(runs), Bitmap(bits)) | (Bitmap(bits), Run(runs)) => {
+                let mut bits = bits.clone();
+                BitXorAssign::bitxor_assign(&mut bits, runs);
+                Bitmap(bits)
+            }
             _ => {
                 let mut lhs = self.clone();
                 BitXorAssign::bitxor_assign(&mut lhs, rhs);
@@ -388,19 +613,25 @@ impl BitXor<&Store> for &Store {
 impl BitXorAssign<Store> for Store {
     fn bitxor_assign(&mut self, mut rhs: Store) {
         match (self, &mut rhs) {
-            (&mut Array(ref mut vec1), &mut Array(ref vec2)) => {
-                *vec1 = BitXor::bitxor(&*vec1, vec2);
+            (Array(vec1), Array(vec2)) => {
+                *vec1 = BitXor::bitxor(&*vec1, &*vec2);
             }
-            (&mut Bitmap(ref mut bits1), &mut Array(ref vec2)) => {
-                BitXorAssign::bitxor_assign(bits1, vec2);
+            (Bitmap(bits1), Array(vec2)) => {
+                BitXorAssign::bitxor_assign(bits1, &*vec2);
             }
-            (&mut Bitmap(ref mut bits1), &mut Bitmap(ref bits2)) => {
-                BitXorAssign::bitxor_assign(bits1, bits2);
+            (Bitmap(bits1), Bitmap(bits2)) => {
+                BitXorAssign::bitxor_assign(bits1, &*bits2);
+            }
+            (Bitmap(bits1), Run(runs2)) => {
+                BitXorAssign::bitxor_assign(bits1, &*runs2);
             }
-            (this @ &mut Array(..), &mut Bitmap(..)) => {
+            (this @ Array(..), Bitmap(..)) | (this @ Run(..), Bitmap(..)) => {
                 mem::swap(this, &mut rhs);
                 BitXorAssign::bitxor_assign(this, rhs);
             }
+            (this @ Run(..), _) | (this @ Array(..), Run(..)) => {
+                *this = BitXor::bitxor(&*this, &rhs);
+            }
         }
     }
 }
@@ -408,21 +639,27 @@ impl BitXorAssign<Store> for Store {
 impl BitXorAssign<&Store> for Store {
     fn bitxor_assign(&mut self, rhs: &Store) {
         match (self, rhs) {
-            (&mut Array(ref mut vec1), &Array(ref vec2)) => {
+            (Array(vec1), Array(vec2)) => {
                 let this = mem::take(vec1);
                 *vec1 = BitXor::bitxor(&this, vec2);
             }
-            (&mut Bitmap(ref mut bits1), &Array(ref vec2)) => {
+            (Bitmap(bits1), Array(vec2)) => {
                 BitXorAssign::bitxor_assign(bits1, vec2);
             }
-            (&mut Bitmap(ref mut bits1), &Bitmap(ref bits2)) => {
+            (Bitmap(bits1), Bitmap(bits2)) => {
                 BitXorAssign::bitxor_assign(bits1, bits2);
             }
-            (this @ &mut Array(..), &Bitmap(ref bits2)) => {
+            (Bitmap(bits1), Run(runs2)) => {
+                BitXorAssign::bitxor_assign(bits1, runs2);
+            }
+            (this @ Array(..), Bitmap(bits2)) | (this @ Run(..), Bitmap(bits2)) => {
                 let mut lhs: Store = Bitmap(bits2.clone());
                 BitXorAssign::bitxor_assign(&mut lhs, &*this);
                 *this = lhs;
             }
+            (this @ Run(..), _) | (this @ Array(..), Run(..)) => {
+                *this = BitXor::bitxor(&*this, rhs);
+            }
         }
     }
 }
@@ -434,6 +671,7 @@ impl<'a> IntoIterator for &'a Store {
         match self {
             Array(vec) => Iter::Array(vec.iter()),
             Bitmap(bits) => Iter::BitmapBorrowed(bits.iter()),
+            Run(runs) => Iter::RunBorrowed(runs.iter()),
         }
     }
 }
@@ -445,6 +683,7 @@ impl IntoIterator for Store {
         match self {
             Array(vec) => Iter::Vec(vec.into_iter()),
             Bitmap(bits) => Iter::BitmapOwned(bits.into_iter()),
+            Run(runs) => Iter::RunOwned(runs.into_iter()),
         }
     }
 }
@@ -457,6 +696,10 @@ impl PartialEq for Store {
                 bits1.len() == bits2.len()
                     && bits1.iter().zip(bits2.iter()).all(|(i1, i2)| i1 == i2)
             }
+            (Run(runs1), Run(runs2)) => runs1 == runs2,
+            // Run containers are not normalized, the same values may be
+            // stored in any of the representations.
+            (Run(..), _) | (_, Run(..)) => self.len() == other.len() && self.into_iter().eq(other),
             _ => false,
         }
     }
@@ -471,6 +714,8 @@ impl<'a> Iterator for Iter<'a> {
             Iter::Vec(inner) => inner.next(),
             Iter::BitmapBorrowed(inner) => inner.next(),
             Iter::BitmapOwned(inner) => inner.next(),
+            Iter::RunBorrowed(inner) => inner.next(),
+            Iter::RunOwned(inner) => inner.next(),
         }
     }
 }
@@ -482,6 +727,109 @@ impl DoubleEndedIterator for Iter<'_> {
             Iter::Vec(inner) => inner.next_back(),
             Iter::BitmapBorrowed(inner) => inner.next_back(),
             Iter::BitmapOwned(inner) => inner.next_back(),
+            Iter::RunBorrowed(inner) => inner.next_back(),
+            Iter::RunOwned(inner) => inner.next_back(),
+        }
+    }
+}
+
+impl Iterator for Ranges<'_> {
+    type Item = RangeInclusive<u16>;
+
+    fn next(&mut self) -> Option<RangeInclusive<u16>> {
+        match self {
+            Ranges::Array(inner) => inner.next(),
+            Ranges::Bitmap(inner) => inner.next(),
+            Ranges::Run(inner) => inner.next().map(|iv| iv.start..=iv.end()),
         }
     }
 }
+
+impl DoubleEndedIterator for Ranges<'_> {
+    fn next_back(&mut self) -> Option<RangeInclusive<u16>> {
+        match self {
+            Ranges::Array(inner) => inner.next_back(),
+            Ranges::Bitmap(inner) => inner.next_back(),
+            Ranges::Run(inner) => inner.next_back().map(|iv| iv.start..=iv.end()),
+        }
+    }
+}
+
+impl Iter<'_> {
+    /// Writes the next values, combined with `high`, into `buf`,
+    /// returns the number of written values.
+    pub fn next_many(&mut self, high: u32, buf: &mut [u32]) -> usize {
+        match self {
+            Iter::Array(inner) => {
+                let n = copy_slice(inner.as_slice(), high, buf);
+                skip(inner, n) as usize
+            }
+            Iter::Vec(inner) => {
+                let n = copy_slice(inner.as_slice(), high, buf);
+                skip(inner, n) as usize
+            }
+            Iter::BitmapBorrowed(inner) => inner.next_many(high, buf),
+            Iter::BitmapOwned(inner) => inner.next_many(high, buf),
+            Iter::RunBorrowed(inner) => inner.next_many(high, buf),
+            Iter::RunOwned(inner) => inner.next_many(high, buf),
+        }
+    }
+
+    /// Skips the values lower than `index`, returns the number of skipped values.
+    pub fn advance_to(&mut self, index: u16) -> u64 {
+        match self {
+            Iter::Array(inner) => {
+                let n = inner.as_slice().partition_point(|&value| value < index);
+                skip(inner, n)
+            }
+            Iter::Vec(inner) => {
+                let n = inner.as_slice().partition_point(|&value| value < index);
+                skip(inner, n)
+            }
+            Iter::BitmapBorrowed(inner) => inner.advance_to(index),
+            Iter::BitmapOwned(inner) => inner.advance_to(index),
+            Iter::RunBorrowed(inner) => inner.advance_to(index),
+            Iter::RunOwned(inner) => inner.advance_to(index),
+        }
+    }
+
+    /// Skips the values greater than `index` from the back, returns the number of skipped values.
+    pub fn advance_back_to(&mut self, index: u16) -> u64 {
+        match self {
+            Iter::Array(inner) => {
+                let n = inner.len() - inner.as_slice().partition_point(|&value| value <= index);
+                skip_back(inner, n)
+            }
+            Iter::Vec(inner) => {
+                let n = inner.len() - inner.as_slice().partition_point(|&value| value <= index);
+                skip_back(inner, n)
+            }
+            Iter::BitmapBorrowed(inner) => inner.advance_back_to(index),
+            Iter::BitmapOwned(inner) => inner.advance_back_to(index),
+            Iter::RunBorrowed(inner) => inner.advance_back_to(index),
+            Iter::RunOwned(inner) => inner.advance_back_to(index),
+        }
+    }
+}
+
+fn copy_slice(values: &[u16], high: u32, buf: &mut [u32]) -> usize {
+    let n = values.len().min(buf.len());
+    for (slot, &value) in buf.iter_mut().zip(&values[..n]) {
+        *slot = high | u32::from(value);
+    }
+    n
+}
+
+fn skip<I: Iterator>(iter: &mut I, n: usize) -> u64 {
+    if n > 0 {
+        iter.nth(n - 1);
+    }
+    n as u64
+}
+
+fn skip_back<I: DoubleEndedIterator>(iter: &mut I, n: usize) -> u64 {
+    if n > 0 {
+        iter.nth_back(n - 1);
+    }
+    n as u64
+}
@@ -0,0 +1,953 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::mem;
+use std::ops::{BitAnd, BitOr, BitXor, RangeInclusive, Sub};
+
+use super::{ArrayStore, BitmapStore};
+use crate::DeserializeError;
+
+/// A run of consecutive values, stored the same way as in the serialized format:
+/// the run covers `start..=start + length`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Interval {
+    pub start: u16,
+    pub length: u16,
+}
+
+impl Interval {
+    pub fn new(start: u16, end: u16) -> Interval {
+        debug_assert!(start <= end);
+        Interval { start, length: end - start }
+    }
+
+    pub fn end(&self) -> u16 {
+        self.start + self.length
+    }
+
+    pub fn len(&self) -> u64 {
+        u64::from(self.length) + 1
+    }
+
+    fn overlap_len(&self, start: u16, end: u16) -> u64 {
+        let start = self.start.max(start);
+        let end = self.end().min(end);
+        if start <= end {
+            u64::from(end - start) + 1
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct RunStore {
+    vec: Vec<Interval>,
+}
+
+impl RunStore {
+    pub fn new() -> RunStore {
+        RunStore { vec: vec![] }
+    }
+
+    pub fn full() -> RunStore {
+        RunStore { vec: vec![Interval::new(0, u16::MAX)] }
+    }
+
+    ///
+    /// Create a new RunStore from a given vec of intervals
+    /// It is up to the caller to ensure the intervals are sorted, do not overlap
+    /// and are not adjacent to each other
+    /// Favor `try_from` / `try_into` for cases in which these invariants should be checked
+    ///
+    /// # Panics
+    ///
+    /// When debug_assertions are enabled and the above invariants are not met
+    #[inline]
+    pub fn from_vec_unchecked(vec: Vec<Interval>) -> RunStore {
+        if cfg!(debug_assertions) {
+            RunStore::try_from(vec).unwrap()
+        } else {
+            RunStore { vec }
+        }
+    }
+
+    /// Checks the intervals like `try_from` and that they contain exactly `len` values.
+    pub fn try_from_len(len: u64, intervals: Vec<Interval>) -> Result<RunStore, Error> {
+        let store = RunStore::try_from(intervals)?;
+        let actual = store.len();
+        if len != actual {
+            Err(Error { kind: ErrorKind::Cardinality { expected: len, actual } })
+        } else {
+            Ok(store)
+        }
+    }
+
+    pub fn insert(&mut self, index: u16) -> bool {
+        let i = match self.vec.binary_search_by_key(&index, |iv| iv.start) {
+            Ok(_) => return false,
+            Err(i) => i,
+        };
+
+        // `i` is the number of intervals starting before `index`
+        if i > 0 && self.vec[i - 1].end() >= index {
+            return false;
+        }
+
+        let joins_prev = i > 0 && u32::from(self.vec[i - 1].end()) + 1 == u32::from(index);
+        let joins_next = i < self.vec.len() && u32::from(self.vec[i].start) == u32::from(index) + 1;
+
+        match (joins_prev, joins_next) {
+            (true, true) => {
+                let next = self.vec.remove(i);
+                let prev = &mut self.vec[i - 1];
+                prev.length = next.end() - prev.start;
+            }
+            (true, false) => self.vec[i - 1].length += 1,
+            (false, true) => {
+                let next = &mut self.vec[i];
+                next.start = index;
+                next.length += 1;
+            }
+            (false, false) => self.vec.insert(i, Interval::new(index, index)),
+        }
+
+        true
+    }
+
+    pub fn insert_range(&mut self, range: RangeInclusive<u16>) -> u64 {
+        let start = *range.start();
+        let end = *range.end();
+
+        // The intervals overlapping or adjacent to the range are all merged into one
+        let first = self.vec.partition_point(|iv| u32::from(iv.end()) + 1 < u32::from(start));
+        let last = self.vec.partition_point(|iv| u32::from(iv.start) <= u32::from(end) + 1);
+
+        let merged = &self.vec[first..last];
+        let existed: u64 = merged.iter().map(|iv| iv.overlap_len(start, end)).sum();
+        let new_start = merged.first().map_or(start, |iv| iv.start.min(start));
+        let new_end = merged.last().map_or(end, |iv| iv.end().max(end));
+
+        self.vec.splice(first..last, Some(Interval::new(new_start, new_end)));
+
+        u64::from(end - start) + 1 - existed
+    }
+
+    pub fn push(&mut self, index: u16) -> bool {
+        if self.max().map_or(true, |max| max < index) {
+            self.push_unchecked(index);
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Pushes `index` at the end of the store.
+    /// It is up to the caller to have validated index > self.max()
+    ///
+    /// # Panics
+    ///
+    /// If debug_assertions enabled and index is > self.max()
+    pub(crate) fn push_unchecked(&mut self, index: u16) {
+        if cfg!(debug_assertions) {
+            if let Some(max) = self.max() {
+                assert!(index > max, "store max >= index")
+            }
+        }
+        match self.vec.last_mut() {
+            Some(last) if u32::from(last.end()) + 1 == u32::from(index) => last.length += 1,
+            _ => self.vec.push(Interval::new(index, index)),
+        }
+    }
+
+    pub fn remove(&mut self, index: u16) -> bool {
+        let i = self.vec.partition_point(|iv| iv.end() < index);
+        let interval = match self.vec.get(i) {
+            Some(iv) if iv.start <= index => *iv,
+            _ => return false,
+        };
+
+        if interval.length == 0 {
+            self.vec.remove(i);
+        } else if interval.start == index {
+            self.vec[i] = Interval::new(index + 1, interval.end());
+        } else if interval.end() == index {
+            self.vec[i].length -= 1;
+        } else {
+            self.vec[i] = Interval::new(interval.start, index - 1);
+            self.vec.insert(i + 1, Interval::new(index + 1, interval.end()));
+        }
+
+        true
+    }
+
+    pub fn remove_range(&mut self, range: RangeInclusive<u16>) -> u64 {
+        let start = *range.start();
+        let end = *range.end();
+
+        let first = self.vec.partition_point(|iv| iv.end() < start);
+        let last = self.vec.partition_point(|iv| iv.start <= end);
+        if first >= last {
+            return 0;
+        }
+
+        let removed = self.vec[first..last].iter().map(|iv| iv.overlap_len(start, end)).sum();
+
+        // Keep the parts of the boundary intervals that are outside of the range
+        let head = self.vec[first];
+        let tail = self.vec[last - 1];
+        let head = (head.start < start).then(|| Interval::new(head.start, start - 1));
+        let tail = (tail.end() > end).then(|| Interval::new(end + 1, tail.end()));
+        self.vec.splice(first..last, head.into_iter().chain(tail));
+
+        removed
+    }
+
+    /// Retains only the values specified by the predicate, the retained
+    /// values of each run are split into new runs.
+    pub fn retain(&mut self, mut f: impl FnMut(u16) -> bool) {
+        let mut vec = Vec::new();
+        for iv in &self.vec {
+            let mut start = None;
+            for value in iv.start..=iv.end() {
+                if f(value) {
+                    start.get_or_insert(value);
+                } else if let Some(start) = start.take() {
+                    vec.push(Interval::new(start, value - 1));
+                }
+            }
+            if let Some(start) = start {
+                vec.push(Interval::new(start, iv.end()));
+            }
+        }
+        self.vec = vec;
+    }
+
+    pub fn contains(&self, index: u16) -> bool {
+        match self.vec.binary_search_by_key(&index, |iv| iv.start) {
+            Ok(_) => true,
+            Err(0) => false,
+            Err(i) => self.vec[i - 1].end() >= index,
+        }
+    }
+
+    pub fn contains_range(&self, range: RangeInclusive<u16>) -> bool {
+        let start = *range.start();
+        let end = *range.end();
+        let i = self.vec.partition_point(|iv| iv.end() < start);
+        self.vec.get(i).map_or(false, |iv| iv.start <= start && iv.end() >= end)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection_len(other) == 0
+    }
+
+    pub fn is_disjoint_array(&self, other: &ArrayStore) -> bool {
+        self.intersection_len_array(other) == 0
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        let mut others = other.vec.iter().peekable();
+        self.vec.iter().all(|iv| {
+            while others.next_if(|o| o.end() < iv.start).is_some() {}
+            others.peek().map_or(false, |o| o.start <= iv.start && o.end() >= iv.end())
+        })
+    }
+
+    pub fn is_subset_array(&self, other: &ArrayStore) -> bool {
+        self.len() <= other.len()
+            && self.vec.iter().all(|iv| other.contains_range(iv.start..=iv.end()))
+    }
+
+    pub fn intersection_len(&self, other: &Self) -> u64 {
+        let (mut i, mut j) = (0, 0);
+        let mut len = 0;
+        while i < self.vec.len() && j < other.vec.len() {
+            let (a, b) = (self.vec[i], other.vec[j]);
+            len += a.overlap_len(b.start, b.end());
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        len
+    }
+
+    pub fn intersection_len_array(&self, other: &ArrayStore) -> u64 {
+        let mut intervals = self.vec.iter().peekable();
+        other
+            .iter()
+            .filter(|&&value| {
+                while intervals.next_if(|iv| iv.end() < value).is_some() {}
+                intervals.peek().map_or(false, |iv| iv.start <= value)
+            })
+            .count() as u64
+    }
+
+    pub fn to_array_store(&self) -> ArrayStore {
+        let mut vec = Vec::with_capacity(self.len() as usize);
+        for iv in &self.vec {
+            vec.extend(iv.start..=iv.end());
+        }
+        ArrayStore::from_vec_unchecked(vec)
+    }
+
+    pub fn to_bitmap_store(&self) -> BitmapStore {
+        let mut bits = BitmapStore::new();
+        for iv in &self.vec {
+            bits.insert_range(iv.start..=iv.end());
+        }
+        bits
+    }
+
+    pub fn len(&self) -> u64 {
+        self.vec.iter().map(Interval::len).sum()
+    }
+
+    /// Returns the number of bytes allocated on the heap, including the spare capacity.
+    pub fn heap_size(&self) -> usize {
+        self.vec.capacity() * mem::size_of::<Interval>()
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.vec.shrink_to_fit();
+    }
+
+    /// Returns the number of intervals in the store.
+    pub fn run_amount(&self) -> u64 {
+        self.vec.len() as u64
+    }
+
+    pub fn min(&self) -> Option<u16> {
+        self.vec.first().map(|iv| iv.start)
+    }
+
+    pub fn max(&self) -> Option<u16> {
+        self.vec.last().map(Interval::end)
+    }
+
+    pub fn next_value(&self, index: u16) -> Option<u16> {
+        let i = self.vec.partition_point(|iv| iv.end() < index);
+        self.vec.get(i).map(|iv| iv.start.max(index))
+    }
+
+    pub fn previous_value(&self, index: u16) -> Option<u16> {
+        let i = self.vec.partition_point(|iv| iv.start <= index);
+        i.checked_sub(1).map(|i| self.vec[i].end().min(index))
+    }
+
+    pub fn next_absent(&self, index: u16) -> Option<u16> {
+        // The runs are sorted, disjoint and never adjacent,
+        // the value following the run containing the index is absent
+        let i = self.vec.partition_point(|iv| iv.end() < index);
+        match self.vec.get(i) {
+            Some(iv) if iv.start <= index => iv.end().checked_add(1),
+            _ => Some(index),
+        }
+    }
+
+    pub fn previous_absent(&self, index: u16) -> Option<u16> {
+        // The runs are sorted, disjoint and never adjacent,
+        // the value preceding the run containing the index is absent
+        let i = self.vec.partition_point(|iv| iv.start <= index);
+        match i.checked_sub(1).map(|i| &self.vec[i]) {
+            Some(iv) if iv.end() >= index => iv.start.checked_sub(1),
+            _ => Some(index),
+        }
+    }
+
+    pub fn rank(&self, index: u16) -> u64 {
+        let i = self.vec.partition_point(|iv| iv.start <= index);
+        self.vec[..i].iter().map(|iv| iv.overlap_len(0, index)).sum()
+    }
+
+    pub fn select(&self, n: u16) -> Option<u16> {
+        let mut n = u64::from(n);
+        for iv in &self.vec {
+            if n < iv.len() {
+                return Some(iv.start + n as u16);
+            }
+            n -= iv.len();
+        }
+        None
+    }
+
+    pub fn iter(&self) -> RunIter<&[Interval]> {
+        RunIter::new(&self.vec)
+    }
+
+    pub fn into_iter(self) -> RunIter<Vec<Interval>> {
+        RunIter::new(self.vec)
+    }
+
+    /// Adds `offset` to the values, returns the values that do not overflow
+    /// and the values that overflow, wrapped around.
+    pub fn add_offset(&self, offset: u16) -> (RunStore, RunStore) {
+        let mut low = Vec::new();
+        let mut high = Vec::new();
+        let max = u16::MAX - offset;
+        for iv in &self.vec {
+            let (start, end) = (iv.start.wrapping_add(offset), iv.end().wrapping_add(offset));
+            if iv.end() <= max {
+                low.push(Interval::new(start, end));
+            } else if iv.start > max {
+                high.push(Interval::new(start, end));
+            } else {
+                low.push(Interval::new(start, u16::MAX));
+                high.push(Interval::new(0, end));
+            }
+        }
+        (RunStore { vec: low }, RunStore { vec: high })
+    }
+
+    pub fn as_slice(&self) -> &[Interval] {
+        &self.vec
+    }
+}
+
+impl Default for RunStore {
+    fn default() -> Self {
+        RunStore::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    Overflow { index: usize },
+    OutOfOrder { index: usize },
+    Overlapping { index: usize },
+    Cardinality { expected: u64, actual: u64 },
+}
+
+impl Error {
+    pub(crate) fn into_deserialize_error(self, key: u16) -> DeserializeError {
+        match self.kind {
+            ErrorKind::Cardinality { expected, actual } => {
+                DeserializeError::CardinalityMismatch { key, expected, actual }
+            }
+            _ => DeserializeError::InvalidRuns { key },
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ErrorKind::Overflow { index } => {
+                write!(f, "An interval ended after u16::MAX at index: {}", index)
+            }
+            ErrorKind::OutOfOrder { index } => {
+                write!(f, "An interval was out of order at index: {}", index)
+            }
+            ErrorKind::Overlapping { index } => {
+                write!(f, "An interval overlapped the previous one at index: {}", index)
+            }
+            ErrorKind::Cardinality { expected, actual } => {
+                write!(f, "Expected cardinality was {} but was {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl TryFrom<Vec<Interval>> for RunStore {
+    type Error = Error;
+
+    /// Checks that the intervals are sorted and do not overlap.
+    /// Intervals that are adjacent to each other are merged.
+    fn try_from(mut value: Vec<Interval>) -> Result<Self, Self::Error> {
+        // The number of intervals kept after merging the adjacent ones
+        let mut len = 0;
+        for index in 0..value.len() {
+            let iv = value[index];
+            if iv.start.checked_add(iv.length).is_none() {
+                return Err(Error { kind: ErrorKind::Overflow { index } });
+            }
+            if len > 0 {
+                let prev = &mut value[len - 1];
+                if iv.start < prev.start {
+                    return Err(Error { kind: ErrorKind::OutOfOrder { index } });
+                } else if iv.start <= prev.end() {
+                    return Err(Error { kind: ErrorKind::Overlapping { index } });
+                } else if u32::from(prev.end()) + 1 == u32::from(iv.start) {
+                    prev.length = iv.end() - prev.start;
+                    continue;
+                }
+            }
+            value[len] = iv;
+            len += 1;
+        }
+        value.truncate(len);
+
+        Ok(RunStore { vec: value })
+    }
+}
+
+/// Pushes the `start..=end` interval at the end of `vec`, merging it with the last
+/// interval when they overlap or are adjacent. Intervals must be pushed ordered by start.
+fn push_interval(vec: &mut Vec<Interval>, start: u16, end: u16) {
+    match vec.last_mut() {
+        Some(last) if u32::from(start) <= u32::from(last.end()) + 1 => {
+            if end > last.end() {
+                last.length = end - last.start;
+            }
+        }
+        _ => vec.push(Interval::new(start, end)),
+    }
+}
+
+/// Iterates over the sorted values of an array as maximal intervals.
+struct ArrayIntervals<'a> {
+    values: &'a [u16],
+}
+
+impl Iterator for ArrayIntervals<'_> {
+    type Item = Interval;
+
+    fn next(&mut self) -> Option<Interval> {
+        let (&start, rest) = self.values.split_first()?;
+        let count =
+            rest.iter().zip(1..).take_while(|&(&v, i)| v as u32 == start as u32 + i).count();
+        self.values = &rest[count..];
+        Some(Interval::new(start, start + count as u16))
+    }
+}
+
+fn intervals(array: &ArrayStore) -> ArrayIntervals<'_> {
+    ArrayIntervals { values: array.as_slice() }
+}
+
+fn or<L, R>(lhs: L, rhs: R) -> RunStore
+where
+    L: Iterator<Item = Interval>,
+    R: Iterator<Item = Interval>,
+{
+    let (mut lhs, mut rhs) = (lhs.peekable(), rhs.peekable());
+    let mut vec = Vec::new();
+    loop {
+        let next = match (lhs.peek(), rhs.peek()) {
+            (Some(a), Some(b)) if a.start <= b.start => lhs.next(),
+            (Some(_), Some(_)) => rhs.next(),
+            (Some(_), None) => lhs.next(),
+            (None, Some(_)) => rhs.next(),
+            (None, None) => break,
+        };
+        let iv = next.unwrap();
+        push_interval(&mut vec, iv.start, iv.end());
+    }
+    RunStore { vec }
+}
+
+fn and<L, R>(lhs: L, rhs: R) -> RunStore
+where
+    L: Iterator<Item = Interval>,
+    R: Iterator<Item = Interval>,
+{
+    let (mut lhs, mut rhs) = (lhs.peekable(), rhs.peekable());
+    let mut vec = Vec::new();
+    while let (Some(&a), Some(&b)) = (lhs.peek(), rhs.peek()) {
+        let start = a.start.max(b.start);
+        let end = a.end().min(b.end());
+        if start <= end {
+            vec.push(Interval::new(start, end));
+        }
+        if a.end() < b.end() {
+            lhs.next();
+        } else {
+            rhs.next();
+        }
+    }
+    RunStore { vec }
+}
+
+fn sub<L, R>(lhs: L, rhs: R) -> RunStore
+where
+    L: Iterator<Item = Interval>,
+    R: Iterator<Item = Interval>,
+{
+    let mut rhs = rhs.peekable();
+    let mut vec = Vec::new();
+    for a in lhs {
+        // The next value of `a` that has not been removed nor kept yet
+        let mut start = u32::from(a.start);
+        while rhs.next_if(|b| u32::from(b.end()) < start).is_some() {}
+        while let Some(&b) = rhs.peek() {
+            if b.start > a.end() {
+                break;
+            }
+            if u32::from(b.start) > start {
+                vec.push(Interval::new(start as u16, b.start - 1));
+            }
+            start = u32::from(b.end()) + 1;
+            if b.end() >= a.end() {
+                break;
+            }
+            rhs.next();
+        }
+        if start <= u32::from(a.end()) {
+            vec.push(Interval::new(start as u16, a.end()));
+        }
+    }
+    RunStore { vec }
+}
+
+fn xor<L, R>(lhs: L, rhs: R) -> RunStore
+where
+    L: Iterator<Item = Interval>,
+    R: Iterator<Item = Interval>,
+{
+    // Every interval toggles membership at its start and right after its end,
+    // the values toggled an odd number of times are the symmetric difference.
+    fn bounds(iter: impl Iterator<Item = Interval>) -> impl Iterator<Item = u32> {
+        iter.flat_map(|iv| [u32::from(iv.start), u32::from(iv.end()) + 1])
+    }
+
+    let (mut lhs, mut rhs) = (bounds(lhs).peekable(), bounds(rhs).peekable());
+    let mut vec = Vec::new();
+    let mut open: Option<u32> = None;
+    loop {
+        let bound = match (lhs.peek(), rhs.peek()) {
+            (Some(a), Some(b)) => match a.cmp(b) {
+                Ordering::Less => lhs.next(),
+                Ordering::Greater => rhs.next(),
+                Ordering::Equal => {
+                    lhs.next();
+                    rhs.next();
+                    continue;
+                }
+            },
+            (Some(_), None) => lhs.next(),
+            (None, Some(_)) => rhs.next(),
+            (None, None) => break,
+        };
+        let bound = bound.unwrap();
+        match open.take() {
+            Some(start) => push_interval(&mut vec, start as u16, (bound - 1) as u16),
+            None => open = Some(bound),
+        }
+    }
+    RunStore { vec }
+}
+
+impl BitOr<Self> for &RunStore {
+    type Output = RunStore;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        or(self.vec.iter().copied(), rhs.vec.iter().copied())
+    }
+}
+
+impl BitOr<&ArrayStore> for &RunStore {
+    type Output = RunStore;
+
+    fn bitor(self, rhs: &ArrayStore) -> Self::Output {
+        or(self.vec.iter().copied(), intervals(rhs))
+    }
+}
+
+impl BitAnd<Self> for &RunStore {
+    type Output = RunStore;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        and(self.vec.iter().copied(), rhs.vec.iter().copied())
+    }
+}
+
+impl Sub<Self> for &RunStore {
+    type Output = RunStore;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        sub(self.vec.iter().copied(), rhs.vec.iter().copied())
+    }
+}
+
+impl Sub<&ArrayStore> for &RunStore {
+    type Output = RunStore;
+
+    fn sub(self, rhs: &ArrayStore) -> Self::Output {
+        sub(self.vec.iter().copied(), intervals(rhs))
+    }
+}
+
+impl BitXor<Self> for &RunStore {
+    type Output = RunStore;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        xor(self.vec.iter().copied(), rhs.vec.iter().copied())
+    }
+}
+
+impl BitXor<&ArrayStore> for &RunStore {
+    type Output = RunStore;
+
+    fn bitxor(self, rhs: &ArrayStore) -> Self::Output {
+        xor(self.vec.iter().copied(), intervals(rhs))
+    }
+}
+
+pub struct RunIter<B: Borrow<[Interval]>> {
+    intervals: B,
+    front: (usize, u16),
+    back: (usize, u16),
+    remaining: u64,
+}
+
+impl<B: Borrow<[Interval]>> RunIter<B> {
+    fn new(intervals: B) -> RunIter<B> {
+        let slice = intervals.borrow();
+        let back = slice.last().map_or((0, 0), |iv| (slice.len() - 1, iv.length));
+        let remaining = slice.iter().map(Interval::len).sum();
+        RunIter { intervals, front: (0, 0), back, remaining }
+    }
+}
+
+impl<B: Borrow<[Interval]>> Iterator for RunIter<B> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let (i, offset) = self.front;
+        let iv = self.intervals.borrow()[i];
+        self.front = if offset == iv.length { (i + 1, 0) } else { (i, offset + 1) };
+        Some(iv.start + offset)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<B: Borrow<[Interval]>> DoubleEndedIterator for RunIter<B> {
+    fn next_back(&mut self) -> Option<u16> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let (i, offset) = self.back;
+        let iv = self.intervals.borrow()[i];
+        if offset > 0 {
+            self.back = (i, offset - 1);
+        } else if i > 0 {
+            self.back = (i - 1, self.intervals.borrow()[i - 1].length);
+        }
+        Some(iv.start + offset)
+    }
+}
+
+impl<B: Borrow<[Interval]>> RunIter<B> {
+    /// Writes the next values, combined with `high`, into `buf` an interval at a time,
+    /// returns the number of written values.
+    pub fn next_many(&mut self, high: u32, buf: &mut [u32]) -> usize {
+        let intervals = self.intervals.borrow();
+        let mut n = 0;
+        while n < buf.len() && self.remaining > 0 {
+            let (i, offset) = self.front;
+            let iv = intervals[i];
+            let available = u64::from(iv.length - offset) + 1;
+            let count = available.min(self.remaining).min((buf.len() - n) as u64) as usize;
+            let start = u32::from(iv.start + offset);
+            for (slot, value) in buf[n..n + count].iter_mut().zip(start..) {
+                *slot = high | value;
+            }
+            n += count;
+            self.remaining -= count as u64;
+            self.front =
+                if count as u64 == available { (i + 1, 0) } else { (i, offset + count as u16) };
+        }
+        n
+    }
+
+    /// Skips the values lower than `index`, returns the number of skipped values.
+    pub fn advance_to(&mut self, index: u16) -> u64 {
+        let intervals = self.intervals.borrow();
+        let (i, offset) = self.front;
+        if self.remaining == 0 || intervals[i].start + offset >= index {
+            return 0;
+        }
+        let j = i + intervals[i..].partition_point(|iv| iv.end() < index);
+        let front = match intervals.get(j) {
+            Some(iv) => (j, index.saturating_sub(iv.start)),
+            None => (j, 0),
+        };
+        if front > self.back {
+            return std::mem::replace(&mut self.remaining, 0);
+        }
+        let skipped = intervals[i..j].iter().map(Interval::len).sum::<u64>() + u64::from(front.1)
+            - u64::from(offset);
+        self.front = front;
+        self.remaining -= skipped;
+        skipped
+    }
+
+    /// Skips the values greater than `index` from the back, returns the number of skipped values.
+    pub fn advance_back_to(&mut self, index: u16) -> u64 {
+        let intervals = self.intervals.borrow();
+        let (i, offset) = self.back;
+        if self.remaining == 0 || intervals[i].start + offset <= index {
+            return 0;
+        }
+        let j = intervals[..=i].partition_point(|iv| iv.start <= index);
+        if j == 0 {
+            return std::mem::replace(&mut self.remaining, 0);
+        }
+        let back = (j - 1, index.min(intervals[j - 1].end()) - intervals[j - 1].start);
+        if back < self.front {
+            return std::mem::replace(&mut self.remaining, 0);
+        }
+        let skipped = intervals[j - 1..i].iter().map(Interval::len).sum::<u64>()
+            + u64::from(offset)
+            - u64::from(back.1);
+        self.back = back;
+        self.remaining -= skipped;
+        skipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitmap::store::Store;
+    use proptest::prelude::*;
+
+    fn runs(values: &[(u16, u16)]) -> RunStore {
+        RunStore::from_vec_unchecked(values.iter().map(|&(s, e)| Interval::new(s, e)).collect())
+    }
+
+    fn values(store: &RunStore) -> Vec<u16> {
+        store.iter().collect()
+    }
+
+    #[test]
+    fn test_run_insert() {
+        let mut store = runs(&[(2, 3), (6, 8)]);
+        assert!(store.insert(0));
+        assert!(!store.insert(2));
+        assert!(store.insert(4));
+        assert!(store.insert(5));
+        assert!(store.insert(10));
+        assert_eq!(store.as_slice(), runs(&[(0, 0), (2, 8), (10, 10)]).as_slice());
+    }
+
+    #[test]
+    fn test_run_insert_range() {
+        let mut store = runs(&[(2, 3), (6, 8), (20, 30)]);
+        assert_eq!(store.insert_range(4..=9), 3);
+        assert_eq!(store.as_slice(), runs(&[(2, 9), (20, 30)]).as_slice());
+        assert_eq!(store.insert_range(0..=u16::MAX), 65536 - 19);
+        assert_eq!(store, RunStore::full());
+    }
+
+    #[test]
+    fn test_run_remove_range() {
+        let mut store = runs(&[(2, 3), (6, 8), (20, 30)]);
+        assert_eq!(store.remove_range(3..=22), 7);
+        assert_eq!(store.as_slice(), runs(&[(2, 2), (23, 30)]).as_slice());
+
+        let mut store = runs(&[(0, u16::MAX)]);
+        assert_eq!(store.remove_range(10..=19), 10);
+        assert!(store.remove(0));
+        assert!(store.remove(u16::MAX));
+        assert!(store.remove(100));
+        assert_eq!(store.as_slice(), runs(&[(1, 9), (20, 99), (101, u16::MAX - 1)]).as_slice());
+    }
+
+    #[test]
+    fn test_run_rank_select() {
+        let store = runs(&[(2, 3), (6, 8), (20, 30)]);
+        assert_eq!(store.rank(0), 0);
+        assert_eq!(store.rank(3), 2);
+        assert_eq!(store.rank(7), 4);
+        assert_eq!(store.rank(u16::MAX), 16);
+        assert_eq!(store.select(0), Some(2));
+        assert_eq!(store.select(4), Some(8));
+        assert_eq!(store.select(15), Some(30));
+        assert_eq!(store.select(16), None);
+    }
+
+    #[test]
+    fn test_run_iter() {
+        let store = runs(&[(2, 3), (6, 8)]);
+        assert_eq!(values(&store), vec![2, 3, 6, 7, 8]);
+        assert_eq!(store.iter().rev().collect::<Vec<_>>(), vec![8, 7, 6, 3, 2]);
+
+        let mut iter = store.iter();
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next_back(), Some(8));
+        assert_eq!(iter.next_back(), Some(7));
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next(), Some(6));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn test_run_ops() {
+        let a = runs(&[(0, 9), (20, 29)]);
+        let b = runs(&[(5, 24), (30, 30)]);
+        assert_eq!((&a | &b).as_slice(), runs(&[(0, 30)]).as_slice());
+        assert_eq!((&a & &b).as_slice(), runs(&[(5, 9), (20, 24)]).as_slice());
+        assert_eq!((&a - &b).as_slice(), runs(&[(0, 4), (25, 29)]).as_slice());
+        assert_eq!((&a ^ &b).as_slice(), runs(&[(0, 4), (10, 19), (25, 30)]).as_slice());
+    }
+
+    #[test]
+    fn test_run_try_from() {
+        let merged = RunStore::try_from(vec![Interval::new(0, 1), Interval::new(2, 3)]).unwrap();
+        assert_eq!(merged.as_slice(), &[Interval::new(0, 3)]);
+        assert!(RunStore::try_from(vec![Interval::new(4, 5), Interval::new(0, 1)]).is_err());
+        assert!(RunStore::try_from(vec![Interval::new(0, 4), Interval::new(3, 5)]).is_err());
+        assert!(RunStore::try_from(vec![Interval { start: u16::MAX, length: 1 }]).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn run_conversions_roundtrip(runs in RunStore::arbitrary()) {
+            let bits = runs.to_bitmap_store();
+            let array = runs.to_array_store();
+            prop_assert_eq!(bits.run_amount(), runs.run_amount());
+            prop_assert_eq!(array.run_amount(), runs.run_amount());
+            prop_assert_eq!(bits.to_run_store(), runs.clone());
+            prop_assert_eq!(array.to_run_store(), runs);
+        }
+
+        #[test]
+        fn run_ops_match_bitmap_ops(
+            a in RunStore::arbitrary(),
+            b in RunStore::arbitrary(),
+            c in ArrayStore::arbitrary(),
+        ) {
+            let (a, b, c) = (Store::Run(a), Store::Run(b), Store::Array(c));
+            let (ab, bb, cb) = (a.to_bitmap(), b.to_bitmap(), c.to_bitmap());
+            let values = |store: Store| store.into_iter().collect::<Vec<u16>>();
+            let cases = [
+                (&a, &b, &ab, &bb),
+                (&a, &c, &ab, &cb),
+                (&c, &a, &cb, &ab),
+                (&a, &bb, &ab, &bb),
+                (&bb, &a, &bb, &ab),
+            ];
+            for (lhs, rhs, lhsb, rhsb) in cases {
+                prop_assert_eq!(values(lhs | rhs), values(lhsb | rhsb));
+                prop_assert_eq!(values(lhs & rhs), values(lhsb & rhsb));
+                prop_assert_eq!(values(lhs - rhs), values(lhsb - rhsb));
+                prop_assert_eq!(values(lhs ^ rhs), values(lhsb ^ rhsb));
+                prop_assert_eq!(lhs.intersection_len(rhs), lhsb.intersection_len(rhsb));
+                prop_assert_eq!(lhs.is_subset(rhs), lhsb.is_subset(rhsb));
+                prop_assert_eq!(lhs.is_disjoint(rhs), lhsb.is_disjoint(rhsb));
+            }
+        }
+    }
+}
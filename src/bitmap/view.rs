@@ -0,0 +1,1046 @@
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::io;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+use std::slice::ChunksExact;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use super::container::Container;
+use super::serialization::{
+    ARRAY_CONTAINER_TYPE, BITSET_CONTAINER_TYPE, FROZEN_COOKIE, NO_OFFSET_THRESHOLD,
+    RUN_CONTAINER_TYPE, SERIAL_COOKIE, SERIAL_COOKIE_NO_RUNCONTAINER,
+};
+use super::store::{ArrayStore, BitmapStore, Interval, RunStore, Store};
+use super::util;
+use crate::{DeserializeError, RoaringBitmap, ValidationReport};
+
+/// A read-only bitmap borrowing its containers from bytes in [the standard Roaring on-disk
+/// format][format], the equivalent of Java's `ImmutableRoaringBitmap`. A view can also be
+/// created over the CRoaring frozen format with [`RoaringBitmap::frozen_view`].
+///
+/// Building a view only checks the headers and that every container lies within the
+/// bytes, the containers are read in place when queried. Much like
+/// [`RoaringBitmap::deserialize_unchecked_from`], the values inside of the containers
+/// are not checked: invalid data gives meaningless results but is memory safe. Converting
+/// invalid containers to owned ones, with [`RoaringBitmapView::to_bitmap`] or the operators,
+/// panics in debug builds where the stores check their invariants.
+///
+/// [format]: https://github.com/RoaringBitmap/RoaringFormatSpec
+///
+/// # Examples
+///
+/// ```rust
+/// use roaring::{RoaringBitmap, RoaringBitmapView};
+///
+/// let rb: RoaringBitmap = (1..4).chain(100_000..200_000).collect();
+/// let mut bytes = vec![];
+/// rb.serialize_into(&mut bytes).unwrap();
+///
+/// let view = RoaringBitmapView::new(&bytes).unwrap();
+/// assert!(view.contains(3));
+/// assert_eq!(view.len(), rb.len());
+/// assert!(view.iter().eq(rb.iter()));
+/// ```
+#[derive(Clone)]
+pub struct RoaringBitmapView<'a> {
+    bytes: &'a [u8],
+    layout: Layout<'a>,
+}
+
+#[derive(Clone)]
+enum Layout<'a> {
+    /// The portable format shared by all the implementations.
+    Portable { run_flags: &'a [u8], descriptions: &'a [u8], offsets: Offsets<'a> },
+    /// The frozen format of CRoaring, where the containers are grouped by type.
+    Frozen { keys: &'a [u8], containers: Vec<FrozenContainer> },
+}
+
+#[derive(Clone)]
+enum Offsets<'a> {
+    /// The offset header of the serialized bitmap.
+    Header(&'a [u8]),
+    /// Bitmaps with few run containers are serialized without an offset header.
+    Computed(Vec<u32>),
+}
+
+#[derive(Clone, Copy)]
+struct FrozenContainer {
+    typecode: u8,
+    offset: u32,
+    len: u32,
+    runs: u16,
+}
+
+#[derive(Clone, Copy)]
+enum ContainerView<'a> {
+    Array(&'a [u8]),
+    Bitmap(&'a [u8]),
+    /// The runs of the container, without the leading number of runs.
+    Run(&'a [u8]),
+}
+
+impl<'a> RoaringBitmapView<'a> {
+    /// Creates a view over a bitmap serialized in [the standard Roaring on-disk
+    /// format][format], as written by [`RoaringBitmap::serialize_into`] or by the
+    /// C/C++, Java and Go implementations.
+    ///
+    /// [format]: https://github.com/RoaringBitmap/RoaringFormatSpec
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::{RoaringBitmap, RoaringBitmapView};
+    ///
+    /// let rb: RoaringBitmap = (1..4).collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let view = RoaringBitmapView::new(&bytes).unwrap();
+    /// assert_eq!(view.to_bitmap(), rb);
+    /// assert!(RoaringBitmapView::new(&bytes[..10]).is_err());
+    /// ```
+    pub fn new(bytes: &'a [u8]) -> Result<RoaringBitmapView<'a>, DeserializeError> {
+        let mut reader = bytes;
+
+        let (size, has_offsets, has_run_containers) = {
+            let cookie = reader.read_u32::<LittleEndian>()?;
+            if cookie == SERIAL_COOKIE_NO_RUNCONTAINER {
+                (reader.read_u32::<LittleEndian>()? as usize, true, false)
+            } else if (cookie as u16) == SERIAL_COOKIE {
+                let size = ((cookie >> 16) + 1) as usize;
+                (size, size >= NO_OFFSET_THRESHOLD, true)
+            } else {
+                return Err(DeserializeError::UnknownCookie(cookie));
+            }
+        };
+
+        if size > u16::MAX as usize + 1 {
+            return Err(DeserializeError::TooManyContainers);
+        }
+
+        let run_flags = if has_run_containers { take(&mut reader, (size + 7) / 8)? } else { &[] };
+        let descriptions = take(&mut reader, size * 4)?;
+        let header = if has_offsets { Some(take(&mut reader, size * 4)?) } else { None };
+
+        // Check that the keys are ordered and that the containers are within the bytes
+        let mut position = bytes.len() - reader.len();
+        let mut computed = Vec::new();
+        for i in 0..size {
+            let key = read_u16(descriptions, i * 2);
+            if i > 0 && read_u16(descriptions, (i - 1) * 2) >= key {
+                return Err(DeserializeError::UnsortedKeys { key });
+            }
+            let cardinality = u64::from(read_u16(descriptions, i * 2 + 1)) + 1;
+            let offset = match header {
+                Some(header) => read_u32(header, i) as usize,
+                None => {
+                    computed.push(position as u32);
+                    position
+                }
+            };
+            let payload_len = if is_run(run_flags, i) {
+                let runs = bytes.get(offset..offset + 2).ok_or_else(unexpected_eof)?;
+                2 + 4 * usize::from(u16::from_le_bytes([runs[0], runs[1]]))
+            } else if cardinality <= 4096 {
+                2 * cardinality as usize
+            } else {
+                8 * 1024
+            };
+            if offset.checked_add(payload_len).map_or(true, |end| end > bytes.len()) {
+                return Err(unexpected_eof());
+            }
+            position = offset + payload_len;
+        }
+
+        let offsets = match header {
+            Some(header) => Offsets::Header(header),
+            None => Offsets::Computed(computed),
+        };
+        Ok(RoaringBitmapView {
+            bytes,
+            layout: Layout::Portable { run_flags, descriptions, offsets },
+        })
+    }
+
+    /// Creates a view over a bitmap in the CRoaring frozen format, see
+    /// [`RoaringBitmap::frozen_view`].
+    pub(crate) fn frozen(bytes: &'a [u8]) -> Result<RoaringBitmapView<'a>, DeserializeError> {
+        // The header is at the very end of the buffer
+        let header = match bytes.len().checked_sub(4) {
+            Some(start) => read_u32(&bytes[start..], 0),
+            None => return Err(unexpected_eof()),
+        };
+        if header & 0x7FFF != u32::from(FROZEN_COOKIE) {
+            return Err(DeserializeError::UnknownCookie(header));
+        }
+        let size = (header >> 15) as usize;
+        if size > u16::MAX as usize + 1 {
+            return Err(DeserializeError::TooManyContainers);
+        }
+
+        // Then come the keys, the counts and the typecodes of the containers
+        let metadata_len = 4 + 5 * size;
+        let zones_len = bytes.len().checked_sub(metadata_len).ok_or_else(unexpected_eof)?;
+        let keys = &bytes[zones_len..zones_len + 2 * size];
+        let counts = &bytes[zones_len + 2 * size..zones_len + 4 * size];
+        let typecodes = &bytes[zones_len + 4 * size..zones_len + 5 * size];
+
+        // The containers are stored grouped by type: bitsets, then runs and then arrays
+        let (mut bitset_zone, mut run_zone, mut array_zone) = (0, 0, 0);
+        for (i, &typecode) in typecodes.iter().enumerate() {
+            let key = read_u16(keys, i);
+            let count = usize::from(read_u16(counts, i));
+            match typecode {
+                BITSET_CONTAINER_TYPE => bitset_zone += 8 * 1024,
+                RUN_CONTAINER_TYPE if count == 0 => {
+                    return Err(DeserializeError::InvalidRuns { key })
+                }
+                RUN_CONTAINER_TYPE => run_zone += 4 * count,
+                ARRAY_CONTAINER_TYPE if count >= 4096 => {
+                    return Err(DeserializeError::OversizedArray { key })
+                }
+                ARRAY_CONTAINER_TYPE => array_zone += 2 * (count + 1),
+                _ => return Err(DeserializeError::UnknownContainerType(typecode)),
+            }
+        }
+        let expected = bitset_zone + run_zone + array_zone;
+        if expected != zones_len {
+            return Err(DeserializeError::LengthMismatch { expected, actual: zones_len });
+        }
+
+        let mut offsets = [0, bitset_zone, bitset_zone + run_zone];
+        let mut containers = Vec::with_capacity(size);
+        for (i, &typecode) in typecodes.iter().enumerate() {
+            let key = read_u16(keys, i);
+            if i > 0 && read_u16(keys, i - 1) >= key {
+                return Err(DeserializeError::UnsortedKeys { key });
+            }
+            let count = read_u16(counts, i);
+            let zone = match typecode {
+                BITSET_CONTAINER_TYPE => &mut offsets[0],
+                RUN_CONTAINER_TYPE => &mut offsets[1],
+                _ => &mut offsets[2],
+            };
+            let offset = *zone;
+            let (len, runs) = match typecode {
+                BITSET_CONTAINER_TYPE => {
+                    *zone += 8 * 1024;
+                    (u32::from(count) + 1, 0)
+                }
+                RUN_CONTAINER_TYPE => {
+                    *zone += 4 * usize::from(count);
+                    let runs = &bytes[offset..*zone];
+                    let len = (0..runs.len() / 4).map(|i| u32::from(read_u16(runs, i * 2 + 1)) + 1);
+                    (len.sum(), count)
+                }
+                _ => {
+                    *zone += 2 * (usize::from(count) + 1);
+                    (u32::from(count) + 1, 0)
+                }
+            };
+            containers.push(FrozenContainer { typecode, offset: offset as u32, len, runs });
+        }
+
+        Ok(RoaringBitmapView { bytes, layout: Layout::Frozen { keys, containers } })
+    }
+
+    /// Returns `true` if this bitmap contains the specified integer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::{RoaringBitmap, RoaringBitmapView};
+    ///
+    /// let rb: RoaringBitmap = (1..4).collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let view = RoaringBitmapView::new(&bytes).unwrap();
+    /// assert_eq!(view.contains(0), false);
+    /// assert_eq!(view.contains(1), true);
+    /// ```
+    pub fn contains(&self, value: u32) -> bool {
+        let (key, index) = util::split(value);
+        match self.find(key) {
+            Ok(i) => self.container(i).contains(index),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the number of distinct integers in the bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::{RoaringBitmap, RoaringBitmapView};
+    ///
+    /// let rb: RoaringBitmap = (1..4).collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let view = RoaringBitmapView::new(&bytes).unwrap();
+    /// assert_eq!(view.len(), 3);
+    /// ```
+    pub fn len(&self) -> u64 {
+        (0..self.container_amount()).map(|i| self.cardinality(i)).sum()
+    }
+
+    /// Returns `true` if there are no integers in the bitmap.
+    pub fn is_empty(&self) -> bool {
+        self.container_amount() == 0
+    }
+
+    /// Returns the number of integers that are <= value. rank(u32::MAX) == len()
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::{RoaringBitmap, RoaringBitmapView};
+    ///
+    /// let rb: RoaringBitmap = [0, 10, 100].into_iter().collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let view = RoaringBitmapView::new(&bytes).unwrap();
+    /// assert_eq!(view.rank(0), 1);
+    /// assert_eq!(view.rank(50), 2);
+    /// assert_eq!(view.rank(u32::MAX), 3);
+    /// ```
+    pub fn rank(&self, value: u32) -> u64 {
+        let (key, index) = util::split(value);
+        match self.find(key) {
+            Ok(i) => {
+                (0..i).map(|i| self.cardinality(i)).sum::<u64>() + self.container(i).rank(index)
+            }
+            Err(i) => (0..i).map(|i| self.cardinality(i)).sum(),
+        }
+    }
+
+    /// Returns the `n`th integer in the set or `None` if `n >= len()`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::{RoaringBitmap, RoaringBitmapView};
+    ///
+    /// let rb: RoaringBitmap = [0, 10, 100].into_iter().collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let view = RoaringBitmapView::new(&bytes).unwrap();
+    /// assert_eq!(view.select(1), Some(10));
+    /// assert_eq!(view.select(3), None);
+    /// ```
+    pub fn select(&self, n: u32) -> Option<u32> {
+        let mut n = n as u64;
+
+        for i in 0..self.container_amount() {
+            let len = self.cardinality(i);
+            if len > n {
+                let index = self.container(i).select(n as u16);
+                return Some(util::join(self.key(i), index));
+            }
+            n -= len;
+        }
+
+        None
+    }
+
+    /// Iterator over each value stored in the view, guarantees values are ordered by value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::{RoaringBitmap, RoaringBitmapView};
+    ///
+    /// let rb: RoaringBitmap = [1, 2, 3].into_iter().collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let view = RoaringBitmapView::new(&bytes).unwrap();
+    /// let mut iter = view.iter();
+    /// assert_eq!(iter.next(), Some(1));
+    /// assert_eq!(iter.next(), Some(2));
+    /// assert_eq!(iter.next(), Some(3));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter(&self) -> ViewIter<'_> {
+        ViewIter { view: self, next_container: 0, key: 0, inner: None, remaining: self.len() }
+    }
+
+    /// Copies the view into an owned [`RoaringBitmap`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::{RoaringBitmap, RoaringBitmapView};
+    ///
+    /// let rb: RoaringBitmap = (1..4).collect();
+    /// let mut bytes = vec![];
+    /// rb.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let view = RoaringBitmapView::new(&bytes).unwrap();
+    /// assert_eq!(view.to_bitmap(), rb);
+    /// ```
+    pub fn to_bitmap(&self) -> RoaringBitmap {
+        let containers = (0..self.container_amount()).map(|i| self.to_container(i)).collect();
+        RoaringBitmap { containers }
+    }
+
+    fn container_amount(&self) -> usize {
+        match &self.layout {
+            Layout::Portable { descriptions, .. } => descriptions.len() / 4,
+            Layout::Frozen { containers, .. } => containers.len(),
+        }
+    }
+
+    fn key(&self, i: usize) -> u16 {
+        match &self.layout {
+            Layout::Portable { descriptions, .. } => read_u16(descriptions, i * 2),
+            Layout::Frozen { keys, .. } => read_u16(keys, i),
+        }
+    }
+
+    fn cardinality(&self, i: usize) -> u64 {
+        match &self.layout {
+            Layout::Portable { descriptions, .. } => {
+                u64::from(read_u16(descriptions, i * 2 + 1)) + 1
+            }
+            Layout::Frozen { containers, .. } => u64::from(containers[i].len),
+        }
+    }
+
+    fn find(&self, key: u16) -> Result<usize, usize> {
+        let i = partition_point(self.container_amount(), |i| self.key(i) < key);
+        if i < self.container_amount() && self.key(i) == key {
+            Ok(i)
+        } else {
+            Err(i)
+        }
+    }
+
+    fn container(&self, i: usize) -> ContainerView<'a> {
+        match &self.layout {
+            Layout::Portable { run_flags, offsets, .. } => {
+                let offset = match offsets {
+                    Offsets::Header(header) => read_u32(header, i) as usize,
+                    Offsets::Computed(offsets) => offsets[i] as usize,
+                };
+                if is_run(run_flags, i) {
+                    let runs = usize::from(read_u16(&self.bytes[offset..], 0));
+                    ContainerView::Run(&self.bytes[offset + 2..offset + 2 + runs * 4])
+                } else if self.cardinality(i) <= 4096 {
+                    let len = 2 * self.cardinality(i) as usize;
+                    ContainerView::Array(&self.bytes[offset..offset + len])
+                } else {
+                    ContainerView::Bitmap(&self.bytes[offset..offset + 8 * 1024])
+                }
+            }
+            Layout::Frozen { containers, .. } => {
+                let FrozenContainer { typecode, offset, len, runs } = containers[i];
+                let offset = offset as usize;
+                match typecode {
+                    BITSET_CONTAINER_TYPE => {
+                        ContainerView::Bitmap(&self.bytes[offset..offset + 8 * 1024])
+                    }
+                    RUN_CONTAINER_TYPE => {
+                        ContainerView::Run(&self.bytes[offset..offset + 4 * usize::from(runs)])
+                    }
+                    _ => ContainerView::Array(&self.bytes[offset..offset + 2 * len as usize]),
+                }
+            }
+        }
+    }
+
+    fn to_container(&self, i: usize) -> Container {
+        let store = match self.container(i) {
+            ContainerView::Array(bytes) => {
+                let values = bytes.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+                Store::Array(ArrayStore::from_vec_unchecked(values.collect()))
+            }
+            ContainerView::Bitmap(bytes) => {
+                let mut bits = Box::new([0; 1024]);
+                for (word, b) in bits.iter_mut().zip(bytes.chunks_exact(8)) {
+                    *word = u64::from_le_bytes(b.try_into().unwrap());
+                }
+                Store::Bitmap(BitmapStore::from_unchecked(self.cardinality(i), bits))
+            }
+            ContainerView::Run(bytes) => {
+                let intervals = bytes.chunks_exact(4).map(|b| Interval {
+                    start: u16::from_le_bytes([b[0], b[1]]),
+                    length: u16::from_le_bytes([b[2], b[3]]),
+                });
+                Store::Run(RunStore::from_vec_unchecked(intervals.collect()))
+            }
+        };
+        Container { key: self.key(i), store }
+    }
+
+    /// Checks the content of every container of a view over the portable format and that
+    /// the containers are contiguous and cover the bytes up to the end, see
+    /// [`RoaringBitmap::validate_serialized`].
+    pub(crate) fn validate_portable(bytes: &[u8]) -> Result<ValidationReport, DeserializeError> {
+        let view = RoaringBitmapView::new(bytes)?;
+        let (run_flags, descriptions, offsets) = match &view.layout {
+            Layout::Portable { run_flags, descriptions, offsets } => {
+                (run_flags, descriptions, offsets)
+            }
+            Layout::Frozen { .. } => unreachable!("views are created over the portable format"),
+        };
+
+        let mut position = if run_flags.is_empty() { 8 } else { 4 + run_flags.len() };
+        position += descriptions.len();
+        if let Offsets::Header(header) = offsets {
+            position += header.len();
+        }
+
+        let mut cardinality = 0;
+        for i in 0..view.container_amount() {
+            let key = view.key(i);
+            let expected = view.cardinality(i);
+            let offset = match offsets {
+                Offsets::Header(header) => read_u32(header, i) as usize,
+                Offsets::Computed(offsets) => offsets[i] as usize,
+            };
+            if offset != position {
+                return Err(DeserializeError::InvalidOffset { key });
+            }
+            let container = view.container(i);
+            let actual = container.validate(key)?;
+            if actual != expected {
+                return Err(DeserializeError::CardinalityMismatch { key, expected, actual });
+            }
+            position += match container {
+                ContainerView::Run(runs) => 2 + runs.len(),
+                ContainerView::Array(bytes) | ContainerView::Bitmap(bytes) => bytes.len(),
+            };
+            cardinality += actual;
+        }
+
+        if position != bytes.len() {
+            return Err(DeserializeError::LengthMismatch {
+                expected: position,
+                actual: bytes.len(),
+            });
+        }
+        Ok(ValidationReport { containers: view.container_amount(), cardinality, len: position })
+    }
+}
+
+impl<'a> ContainerView<'a> {
+    /// Checks that the values of the container are sorted and returns its cardinality.
+    fn validate(&self, key: u16) -> Result<u64, DeserializeError> {
+        match *self {
+            ContainerView::Array(bytes) => {
+                let mut values = bytes.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+                let mut previous = values.next();
+                for value in values {
+                    if previous >= Some(value) {
+                        return Err(DeserializeError::UnsortedArray { key });
+                    }
+                    previous = Some(value);
+                }
+                Ok(bytes.len() as u64 / 2)
+            }
+            ContainerView::Bitmap(bytes) => Ok(bytes
+                .chunks_exact(8)
+                .map(|b| u64::from(u64::from_le_bytes(b.try_into().unwrap()).count_ones()))
+                .sum()),
+            ContainerView::Run(bytes) => {
+                let mut cardinality = 0;
+                let mut previous_end = None;
+                for b in bytes.chunks_exact(4) {
+                    let (start, length) =
+                        (u16::from_le_bytes([b[0], b[1]]), u16::from_le_bytes([b[2], b[3]]));
+                    let end = start.checked_add(length);
+                    if end.is_none() || previous_end >= Some(start) {
+                        return Err(DeserializeError::InvalidRuns { key });
+                    }
+                    previous_end = end;
+                    cardinality += u64::from(length) + 1;
+                }
+                Ok(cardinality)
+            }
+        }
+    }
+
+    fn contains(&self, index: u16) -> bool {
+        match *self {
+            ContainerView::Array(bytes) => {
+                let len = bytes.len() / 2;
+                let i = partition_point(len, |i| read_u16(bytes, i) < index);
+                i < len && read_u16(bytes, i) == index
+            }
+            ContainerView::Bitmap(bytes) => {
+                let word = read_u64(bytes, usize::from(index / 64));
+                word & (1 << (index % 64)) != 0
+            }
+            ContainerView::Run(bytes) => {
+                let i = partition_point(bytes.len() / 4, |i| read_u16(bytes, i * 2) <= index);
+                i > 0 && {
+                    let (start, length) =
+                        (read_u16(bytes, (i - 1) * 2), read_u16(bytes, i * 2 - 1));
+                    u32::from(index) <= u32::from(start) + u32::from(length)
+                }
+            }
+        }
+    }
+
+    fn rank(&self, index: u16) -> u64 {
+        match *self {
+            ContainerView::Array(bytes) => {
+                partition_point(bytes.len() / 2, |i| read_u16(bytes, i) <= index) as u64
+            }
+            ContainerView::Bitmap(bytes) => {
+                let key = usize::from(index / 64);
+                let below: u64 = (0..key).map(|i| u64::from(read_u64(bytes, i).count_ones())).sum();
+                let mask = u64::MAX >> (63 - index % 64);
+                below + u64::from((read_u64(bytes, key) & mask).count_ones())
+            }
+            ContainerView::Run(bytes) => bytes
+                .chunks_exact(4)
+                .map(|b| (u16::from_le_bytes([b[0], b[1]]), u16::from_le_bytes([b[2], b[3]])))
+                .take_while(|&(start, _)| start <= index)
+                .map(|(start, length)| u64::from(length.min(index - start)) + 1)
+                .sum(),
+        }
+    }
+
+    /// The caller ensures that `n` is lower than the cardinality of the container.
+    fn select(&self, n: u16) -> u16 {
+        match *self {
+            ContainerView::Array(bytes) => read_u16(bytes, usize::from(n)),
+            ContainerView::Bitmap(bytes) => {
+                let mut n = u32::from(n);
+                for i in 0..1024 {
+                    let mut word = read_u64(bytes, i);
+                    let ones = word.count_ones();
+                    if n < ones {
+                        for _ in 0..n {
+                            word &= word - 1;
+                        }
+                        return (i * 64) as u16 + word.trailing_zeros() as u16;
+                    }
+                    n -= ones;
+                }
+                // The container holds less values than its header claims
+                u16::MAX
+            }
+            ContainerView::Run(bytes) => {
+                let mut n = u32::from(n);
+                for b in bytes.chunks_exact(4) {
+                    let (start, length) =
+                        (u16::from_le_bytes([b[0], b[1]]), u16::from_le_bytes([b[2], b[3]]));
+                    if n <= u32::from(length) {
+                        return start + n as u16;
+                    }
+                    n -= u32::from(length) + 1;
+                }
+                // The container holds less values than its header claims
+                u16::MAX
+            }
+        }
+    }
+
+    fn iter(&self) -> ContainerViewIter<'a> {
+        match *self {
+            ContainerView::Array(bytes) => ContainerViewIter::Array(bytes.chunks_exact(2)),
+            ContainerView::Bitmap(bytes) => {
+                ContainerViewIter::Bitmap { bytes, index: 0, word: read_u64(bytes, 0) }
+            }
+            ContainerView::Run(bytes) => {
+                ContainerViewIter::Run { runs: bytes.chunks_exact(4), next: 1, end: 0 }
+            }
+        }
+    }
+}
+
+/// An iterator for `RoaringBitmapView`.
+pub struct ViewIter<'a> {
+    view: &'a RoaringBitmapView<'a>,
+    next_container: usize,
+    key: u16,
+    inner: Option<ContainerViewIter<'a>>,
+    remaining: u64,
+}
+
+enum ContainerViewIter<'a> {
+    Array(ChunksExact<'a, u8>),
+    Bitmap { bytes: &'a [u8], index: usize, word: u64 },
+    Run { runs: ChunksExact<'a, u8>, next: u32, end: u32 },
+}
+
+impl Iterator for ViewIter<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        loop {
+            if let Some(index) = self.inner.as_mut().and_then(Iterator::next) {
+                self.remaining = self.remaining.saturating_sub(1);
+                return Some(util::join(self.key, index));
+            }
+            if self.next_container == self.view.container_amount() {
+                return None;
+            }
+            self.key = self.view.key(self.next_container);
+            self.inner = Some(self.view.container(self.next_container).iter());
+            self.next_container += 1;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.remaining < usize::MAX as u64 {
+            (self.remaining as usize, Some(self.remaining as usize))
+        } else {
+            (usize::MAX, None)
+        }
+    }
+}
+
+impl Iterator for ContainerViewIter<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        match self {
+            ContainerViewIter::Array(chunks) => {
+                chunks.next().map(|b| u16::from_le_bytes([b[0], b[1]]))
+            }
+            ContainerViewIter::Bitmap { bytes, index, word } => {
+                while *word == 0 {
+                    *index += 1;
+                    if *index == 1024 {
+                        return None;
+                    }
+                    *word = read_u64(bytes, *index);
+                }
+                let value = (*index * 64) as u16 + word.trailing_zeros() as u16;
+                *word &= *word - 1;
+                Some(value)
+            }
+            ContainerViewIter::Run { runs, next, end } => {
+                if *next > *end {
+                    let b = runs.next()?;
+                    *next = u32::from(u16::from_le_bytes([b[0], b[1]]));
+                    *end = *next + u32::from(u16::from_le_bytes([b[2], b[3]]));
+                }
+                let value = *next as u16;
+                *next += 1;
+                Some(value)
+            }
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a RoaringBitmapView<'_> {
+    type Item = u32;
+    type IntoIter = ViewIter<'a>;
+
+    fn into_iter(self) -> ViewIter<'a> {
+        self.iter()
+    }
+}
+
+/// A sorted sequence of containers that can be merged with another one without
+/// materializing the containers that are not involved in an operation.
+trait Containers {
+    fn container_amount(&self) -> usize;
+    fn key(&self, i: usize) -> u16;
+    fn container(&self, i: usize) -> Cow<'_, Container>;
+}
+
+impl Containers for RoaringBitmapView<'_> {
+    fn container_amount(&self) -> usize {
+        RoaringBitmapView::container_amount(self)
+    }
+
+    fn key(&self, i: usize) -> u16 {
+        RoaringBitmapView::key(self, i)
+    }
+
+    fn container(&self, i: usize) -> Cow<'_, Container> {
+        Cow::Owned(self.to_container(i))
+    }
+}
+
+impl Containers for RoaringBitmap {
+    fn container_amount(&self) -> usize {
+        self.containers.len()
+    }
+
+    fn key(&self, i: usize) -> u16 {
+        self.containers[i].key
+    }
+
+    fn container(&self, i: usize) -> Cow<'_, Container> {
+        Cow::Borrowed(&self.containers[i])
+    }
+}
+
+/// Merges the containers of both sides with `op`, the containers found in only one of the
+/// sides are kept according to `keep_lhs` and `keep_rhs`.
+fn merge<L, R, F>(lhs: &L, rhs: &R, keep_lhs: bool, keep_rhs: bool, op: F) -> RoaringBitmap
+where
+    L: Containers,
+    R: Containers,
+    F: Fn(&Container, &Container) -> Container,
+{
+    let mut containers = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < lhs.container_amount() || j < rhs.container_amount() {
+        let lkey = (i < lhs.container_amount()).then(|| lhs.key(i));
+        let rkey = (j < rhs.container_amount()).then(|| rhs.key(j));
+        match (lkey, rkey) {
+            (Some(l), Some(r)) if l == r => {
+                let container = op(&lhs.container(i), &rhs.container(j));
+                if container.len() != 0 {
+                    containers.push(container);
+                }
+                i += 1;
+                j += 1;
+            }
+            (Some(l), r) if r.map_or(true, |r| l < r) => {
+                if keep_lhs {
+                    containers.push(lhs.container(i).into_owned());
+                }
+                i += 1;
+            }
+            _ => {
+                if keep_rhs {
+                    containers.push(rhs.container(j).into_owned());
+                }
+                j += 1;
+            }
+        }
+    }
+    RoaringBitmap { containers }
+}
+
+macro_rules! impl_view_ops {
+    ($lhs:ty, $rhs:ty) => {
+        impl BitOr<$rhs> for $lhs {
+            type Output = RoaringBitmap;
+
+            /// An `union` between two sets.
+            fn bitor(self, rhs: $rhs) -> RoaringBitmap {
+                merge(self, rhs, true, true, |a, b| a | b)
+            }
+        }
+
+        impl BitAnd<$rhs> for $lhs {
+            type Output = RoaringBitmap;
+
+            /// An `intersection` between two sets.
+            fn bitand(self, rhs: $rhs) -> RoaringBitmap {
+                merge(self, rhs, false, false, |a, b| a & b)
+            }
+        }
+
+        impl Sub<$rhs> for $lhs {
+            type Output = RoaringBitmap;
+
+            /// A `difference` between two sets.
+            fn sub(self, rhs: $rhs) -> RoaringBitmap {
+                merge(self, rhs, true, false, |a, b| a - b)
+            }
+        }
+
+        impl BitXor<$rhs> for $lhs {
+            type Output = RoaringBitmap;
+
+            /// A `symmetric difference` between two sets.
+            fn bitxor(self, rhs: $rhs) -> RoaringBitmap {
+                merge(self, rhs, true, true, |a, b| a ^ b)
+            }
+        }
+    };
+}
+
+impl_view_ops!(&RoaringBitmapView<'_>, &RoaringBitmapView<'_>);
+impl_view_ops!(&RoaringBitmapView<'_>, &RoaringBitmap);
+impl_view_ops!(&RoaringBitmap, &RoaringBitmapView<'_>);
+
+fn unexpected_eof() -> DeserializeError {
+    DeserializeError::Io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "failed to fill whole buffer",
+    ))
+}
+
+fn is_run(run_flags: &[u8], i: usize) -> bool {
+    run_flags.get(i / 8).map_or(false, |flags| flags & (1 << (i % 8)) != 0)
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], DeserializeError> {
+    if bytes.len() < len {
+        return Err(unexpected_eof());
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn read_u16(bytes: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([bytes[i * 2], bytes[i * 2 + 1]])
+}
+
+fn read_u32(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap())
+}
+
+/// Returns the first index in `0..len` for which `pred` is false, `pred` must be
+/// true for all the indexes before it and false for all the ones after.
+fn partition_point(len: usize, pred: impl Fn(usize) -> bool) -> usize {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod test {
+    use super::RoaringBitmapView;
+    use crate::{DeserializeError, RoaringBitmap};
+    use proptest::prelude::*;
+
+    fn serialize(bitmap: &RoaringBitmap) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bitmap.serialize_into(&mut bytes).unwrap();
+        bytes
+    }
+
+    proptest! {
+        #[test]
+        fn view_matches_bitmap(
+            mut bitmap in RoaringBitmap::arbitrary(),
+            optimize in any::<bool>(),
+            values in proptest::collection::vec(any::<u32>(), 32),
+        ) {
+            if optimize {
+                bitmap.run_optimize();
+            }
+            let bytes = serialize(&bitmap);
+            let view = RoaringBitmapView::new(&bytes).unwrap();
+
+            prop_assert_eq!(view.len(), bitmap.len());
+            prop_assert_eq!(view.is_empty(), bitmap.is_empty());
+            prop_assert_eq!(view.to_bitmap(), bitmap.clone());
+            prop_assert!(view.iter().eq(bitmap.iter()));
+            for value in values.into_iter().chain(bitmap.iter().step_by(97)) {
+                prop_assert_eq!(view.contains(value), bitmap.contains(value));
+                prop_assert_eq!(view.rank(value), bitmap.rank(value));
+                let n = (value as u64 % (bitmap.len() + 1)) as u32;
+                prop_assert_eq!(view.select(n), bitmap.select(n));
+            }
+        }
+
+        #[test]
+        fn view_ops_match_bitmap_ops(
+            mut a in RoaringBitmap::arbitrary(),
+            b in RoaringBitmap::arbitrary(),
+        ) {
+            a.run_optimize();
+            let (bytes_a, bytes_b) = (serialize(&a), serialize(&b));
+            let (view_a, view_b) = (
+                RoaringBitmapView::new(&bytes_a).unwrap(),
+                RoaringBitmapView::new(&bytes_b).unwrap(),
+            );
+
+            prop_assert_eq!(&view_a | &view_b, &a | &b);
+            prop_assert_eq!(&view_a & &view_b, &a & &b);
+            prop_assert_eq!(&view_a - &view_b, &a - &b);
+            prop_assert_eq!(&view_a ^ &view_b, &a ^ &b);
+            prop_assert_eq!(&view_a & &b, &a & &b);
+            prop_assert_eq!(&a - &view_b, &a - &b);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn frozen_view_matches_bitmap(
+            mut bitmap in RoaringBitmap::arbitrary(),
+            values in proptest::collection::vec(any::<u32>(), 32),
+        ) {
+            bitmap.run_optimize();
+            let mut bytes = Vec::new();
+            bitmap.serialize_frozen_into(&mut bytes).unwrap();
+            prop_assert_eq!(bytes.len(), bitmap.frozen_serialized_size());
+            let view = RoaringBitmap::frozen_view(&bytes).unwrap();
+
+            prop_assert_eq!(view.len(), bitmap.len());
+            prop_assert_eq!(view.to_bitmap(), bitmap.clone());
+            prop_assert!(view.iter().eq(bitmap.iter()));
+            for value in values.into_iter().chain(bitmap.iter().step_by(97)) {
+                prop_assert_eq!(view.contains(value), bitmap.contains(value));
+                prop_assert_eq!(view.rank(value), bitmap.rank(value));
+            }
+        }
+    }
+
+    #[test]
+    fn frozen_layout() {
+        let mut bitmap: RoaringBitmap = (1..4).chain(65536..65546).collect();
+        bitmap.run_optimize();
+        let mut bytes = Vec::new();
+        bitmap.serialize_frozen_into(&mut bytes).unwrap();
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            0x00, 0x00, 0x09, 0x00,             // run zone: 0..=9
+            0x01, 0x00, 0x02, 0x00, 0x03, 0x00, // array zone: [1, 2, 3]
+            0x00, 0x00, 0x01, 0x00,             // keys
+            0x02, 0x00, 0x01, 0x00,             // counts: cardinality - 1, number of runs
+            0x02, 0x03,                         // typecodes: array, run
+            0xC6, 0x35, 0x01, 0x00,             // header: 2 << 15 | 13766
+        ];
+        assert_eq!(bytes, expected);
+        assert_eq!(RoaringBitmap::frozen_view(&bytes).unwrap().to_bitmap(), bitmap);
+
+        for len in 1..bytes.len() {
+            assert!(RoaringBitmap::frozen_view(&bytes[len..]).is_err());
+            assert!(RoaringBitmap::frozen_view(&bytes[..len]).is_err());
+        }
+        let mut invalid = bytes.clone();
+        invalid[18] = 0x04;
+        assert!(matches!(
+            RoaringBitmap::frozen_view(&invalid),
+            Err(DeserializeError::UnknownContainerType(4))
+        ));
+    }
+
+    #[test]
+    fn view_rejects_truncated_bytes() {
+        let mut bitmap: RoaringBitmap = (0..10).chain(100_000..200_000).collect();
+        for _ in 0..2 {
+            let bytes = serialize(&bitmap);
+            for len in 0..bytes.len() {
+                assert!(RoaringBitmapView::new(&bytes[..len]).is_err());
+            }
+            bitmap.run_optimize();
+        }
+    }
+
+    #[test]
+    fn view_select_does_not_panic_on_corrupt_cardinality() {
+        let bitmap: RoaringBitmap = (0..10_000).step_by(2).collect();
+        let mut bytes = serialize(&bitmap);
+        // The header claims 6000 values in the bitmap container which only holds 5000
+        bytes[10..12].copy_from_slice(&5999u16.to_le_bytes());
+        let view = RoaringBitmapView::new(&bytes).unwrap();
+
+        assert_eq!(view.len(), 6000);
+        assert_eq!(view.select(4999), Some(9998));
+        assert!(view.select(5500).is_some());
+        assert_eq!(view.select(6000), None);
+    }
+}
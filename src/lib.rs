@@ -16,7 +16,7 @@
 extern crate byteorder;
 
 use std::error::Error;
-use std::fmt;
+use std::{fmt, io};
 
 /// A compressed bitmap using the [Roaring bitmap compression scheme](https://roaringbitmap.org/).
 pub mod bitmap;
@@ -24,7 +24,7 @@ pub mod bitmap;
 /// A compressed bitmap with u64 values.  Implemented as a `BTreeMap` of `RoaringBitmap`s.
 pub mod treemap;
 
-pub use bitmap::RoaringBitmap;
+pub use bitmap::{DeserializeOptions, RoaringBitmap, RoaringBitmapView, ValidationReport};
 pub use treemap::RoaringTreemap;
 
 /// An error type that is returned when an iterator isn't sorted.
@@ -48,6 +48,138 @@ impl fmt::Display for NonSortedIntegers {
 
 impl Error for NonSortedIntegers {}
 
+/// An error type that is returned when deserializing a bitmap fails.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DeserializeError {
+    /// Reading the bytes failed, truncated data is reported
+    /// as an [`io::ErrorKind::UnexpectedEof`] error.
+    Io(io::Error),
+    /// The data does not start with the cookie of a known format.
+    UnknownCookie(u32),
+    /// The data declares more containers than a bitmap can hold.
+    TooManyContainers,
+    /// The keys of the containers are not strictly increasing.
+    UnsortedKeys {
+        /// The first key that is not greater than the previous one.
+        key: u16,
+    },
+    /// The values of an array container are not strictly increasing.
+    UnsortedArray {
+        /// The key of the container.
+        key: u16,
+    },
+    /// The runs of a run container are empty, unsorted, overlapping or go beyond `u16::MAX`.
+    InvalidRuns {
+        /// The key of the container.
+        key: u16,
+    },
+    /// The cardinality announced in the header does not match the content of a container.
+    CardinalityMismatch {
+        /// The key of the container.
+        key: u16,
+        /// The cardinality announced in the header.
+        expected: u64,
+        /// The actual cardinality of the container.
+        actual: u64,
+    },
+    /// The offset of a container does not match the end of the previous one.
+    InvalidOffset {
+        /// The key of the container.
+        key: u16,
+    },
+    /// An array container of a frozen bitmap holds more than 4096 values.
+    OversizedArray {
+        /// The key of the container.
+        key: u16,
+    },
+    /// A frozen bitmap contains a container of an unknown type.
+    UnknownContainerType(u8),
+    /// The length of the data does not match the size of the containers.
+    LengthMismatch {
+        /// The length computed from the headers.
+        expected: usize,
+        /// The length of the data.
+        actual: usize,
+    },
+    /// The data holds more containers than allowed by the [`DeserializeOptions`].
+    ContainerLimitExceeded,
+    /// The data is longer than allowed by the [`DeserializeOptions`].
+    ByteLimitExceeded,
+    /// The data holds more values than allowed by the [`DeserializeOptions`].
+    CardinalityLimitExceeded,
+    /// The data holds run containers which are not allowed by the [`DeserializeOptions`].
+    RunContainersNotAllowed,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeserializeError::Io(error) => write!(f, "{}", error),
+            DeserializeError::UnknownCookie(cookie) => write!(f, "unknown cookie value {}", cookie),
+            DeserializeError::TooManyContainers => write!(f, "size is greater than supported"),
+            DeserializeError::UnsortedKeys { key } => write!(f, "key {} is out of order", key),
+            DeserializeError::UnsortedArray { key } => {
+                write!(f, "values of the array container {} are out of order", key)
+            }
+            DeserializeError::InvalidRuns { key } => {
+                write!(f, "runs of the run container {} are invalid", key)
+            }
+            DeserializeError::CardinalityMismatch { key, expected, actual } => write!(
+                f,
+                "expected cardinality of the container {} was {} but was {}",
+                key, expected, actual
+            ),
+            DeserializeError::InvalidOffset { key } => {
+                write!(f, "offset of the container {} is invalid", key)
+            }
+            DeserializeError::OversizedArray { key } => {
+                write!(f, "array container {} holds more than 4096 values", key)
+            }
+            DeserializeError::UnknownContainerType(typecode) => {
+                write!(f, "unknown container type {}", typecode)
+            }
+            DeserializeError::LengthMismatch { expected, actual } => {
+                write!(f, "expected length was {} but was {}", expected, actual)
+            }
+            DeserializeError::ContainerLimitExceeded => {
+                write!(f, "number of containers exceeds the limit")
+            }
+            DeserializeError::ByteLimitExceeded => write!(f, "number of bytes exceeds the limit"),
+            DeserializeError::CardinalityLimitExceeded => {
+                write!(f, "number of values exceeds the limit")
+            }
+            DeserializeError::RunContainersNotAllowed => {
+                write!(f, "run containers are not allowed")
+            }
+        }
+    }
+}
+
+impl Error for DeserializeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DeserializeError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DeserializeError {
+    fn from(error: io::Error) -> Self {
+        DeserializeError::Io(error)
+    }
+}
+
+impl From<DeserializeError> for io::Error {
+    fn from(error: DeserializeError) -> Self {
+        match error {
+            DeserializeError::Io(error) => error,
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
 /// A [`Iterator::collect`] blanket implementation that provides extra methods for [`RoaringBitmap`]
 /// and [`RoaringTreemap`].
 ///
@@ -76,6 +208,9 @@ pub trait MultiOps<T>: IntoIterator<Item = T> {
     /// The type of output from operations.
     type Output;
 
+    /// The type of output from the cardinality operations.
+    type LenOutput;
+
     /// The `union` between all elements.
     fn union(self) -> Self::Output;
 
@@ -87,4 +222,85 @@ pub trait MultiOps<T>: IntoIterator<Item = T> {
 
     /// The `symmetric difference` between all elements.
     fn symmetric_difference(self) -> Self::Output;
+
+    /// The values that are in at least `k` of the elements.
+    ///
+    /// A `k` of one is the `union` and a `k` equal to the number of elements is the
+    /// `intersection`. A `k` of zero is handled like a `k` of one.
+    ///
+    /// # Examples
+    /// ```
+    /// use roaring::{MultiOps, RoaringBitmap};
+    ///
+    /// let bitmaps = [
+    ///     RoaringBitmap::from_iter(0..20),
+    ///     RoaringBitmap::from_iter(10..30),
+    ///     RoaringBitmap::from_iter(15..40),
+    /// ];
+    ///
+    /// assert!(bitmaps.at_least(2).iter().eq(10..30));
+    /// ```
+    fn at_least(self, k: usize) -> Self::Output;
+
+    /// The len of the `union` between all elements, computed without creating the union.
+    ///
+    /// The elements are merged key by key and only one container of the union is
+    /// allocated at a time. Owned elements are all pulled from the iterator first, their
+    /// containers are dropped as soon as they are counted.
+    ///
+    /// # Examples
+    /// ```
+    /// use roaring::{MultiOps, RoaringBitmap};
+    ///
+    /// let bitmaps = [
+    ///     RoaringBitmap::from_iter(0..20),
+    ///     RoaringBitmap::from_iter(10..30),
+    ///     RoaringBitmap::from_iter(15..40),
+    /// ];
+    ///
+    /// assert_eq!(bitmaps.union_len(), 40);
+    /// ```
+    fn union_len(self) -> Self::LenOutput;
+
+    /// The len of the `intersection` between all elements, computed without creating the
+    /// intersection.
+    ///
+    /// The elements are merged key by key and only one container of the intersection is
+    /// allocated at a time. Owned elements are all pulled from the iterator first, their
+    /// containers are dropped as soon as they are counted.
+    ///
+    /// # Examples
+    /// ```
+    /// use roaring::{MultiOps, RoaringBitmap};
+    ///
+    /// let bitmaps = [
+    ///     RoaringBitmap::from_iter(0..20),
+    ///     RoaringBitmap::from_iter(10..30),
+    ///     RoaringBitmap::from_iter(15..40),
+    /// ];
+    ///
+    /// assert_eq!(bitmaps.intersection_len(), 5);
+    /// ```
+    fn intersection_len(self) -> Self::LenOutput;
+
+    /// The len of the `symmetric difference` between all elements, computed without creating
+    /// the symmetric difference.
+    ///
+    /// The elements are merged key by key and only one container of the symmetric difference is
+    /// allocated at a time. Owned elements are all pulled from the iterator first, their
+    /// containers are dropped as soon as they are counted.
+    ///
+    /// # Examples
+    /// ```
+    /// use roaring::{MultiOps, RoaringBitmap};
+    ///
+    /// let bitmaps = [
+    ///     RoaringBitmap::from_iter(0..20),
+    ///     RoaringBitmap::from_iter(10..30),
+    ///     RoaringBitmap::from_iter(15..40),
+    /// ];
+    ///
+    /// assert_eq!(bitmaps.symmetric_difference_len(), 10 + 5 + 10);
+    /// ```
+    fn symmetric_difference_len(self) -> Self::LenOutput;
 }
@@ -4,6 +4,7 @@ mod test {
     use proptest::collection::btree_map;
     use proptest::prelude::*;
 
+    #[allow(missing_docs)]
     impl RoaringTreemap {
         prop_compose! {
             pub fn arbitrary()(map in btree_map(0u32..=16, RoaringBitmap::arbitrary(), 0usize..=16)) -> RoaringTreemap {
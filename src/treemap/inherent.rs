@@ -1,5 +1,6 @@
 use std::collections::btree_map::{BTreeMap, Entry};
 use std::iter;
+use std::mem;
 use std::ops::RangeBounds;
 
 use crate::RoaringBitmap;
@@ -46,7 +47,7 @@ impl RoaringTreemap {
     /// ```
     pub fn insert(&mut self, value: u64) -> bool {
         let (hi, lo) = util::split(value);
-        self.map.entry(hi).or_insert_with(RoaringBitmap::new).insert(lo)
+        self.map.entry(hi).or_default().insert(lo)
     }
 
     /// Inserts a range of values.
@@ -81,11 +82,11 @@ impl RoaringTreemap {
 
             // Calculate the sub-range from the lower 32 bits
             counter += if hi == end_hi && hi == start_hi {
-                entry.or_insert_with(RoaringBitmap::new).insert_range(start_lo..=end_lo)
+                entry.or_default().insert_range(start_lo..=end_lo)
             } else if hi == start_hi {
-                entry.or_insert_with(RoaringBitmap::new).insert_range(start_lo..=u32::MAX)
+                entry.or_default().insert_range(start_lo..=u32::MAX)
             } else if hi == end_hi {
-                entry.or_insert_with(RoaringBitmap::new).insert_range(0..=end_lo)
+                entry.or_default().insert_range(0..=end_lo)
             } else {
                 // We insert a full bitmap if it doesn't already exist and return the size of it.
                 // But if the bitmap already exists at this spot we replace it with a full bitmap
@@ -122,7 +123,7 @@ impl RoaringTreemap {
     /// ```
     pub fn push(&mut self, value: u64) -> bool {
         let (hi, lo) = util::split(value);
-        self.map.entry(hi).or_insert_with(RoaringBitmap::new).push(lo)
+        self.map.entry(hi).or_default().push(lo)
     }
 
     /// Pushes `value` in the treemap only if it is greater than the current maximum value.
@@ -224,6 +225,255 @@ impl RoaringTreemap {
         removed
     }
 
+    /// Removes the smallest value of the set and returns it, or `None` if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let mut rb: RoaringTreemap = (1..4).collect();
+    /// assert_eq!(rb.pop_min(), Some(1));
+    /// assert_eq!(rb.pop_min(), Some(2));
+    /// assert!(rb.iter().eq([3]));
+    /// ```
+    pub fn pop_min(&mut self) -> Option<u64> {
+        // The empty bitmaps are dropped until one of them yields a value
+        loop {
+            let (&key, bitmap) = self.map.iter_mut().next()?;
+            let value = bitmap.pop_min();
+            if bitmap.is_empty() {
+                self.map.remove(&key);
+            }
+            if let Some(value) = value {
+                return Some(util::join(key, value));
+            }
+        }
+    }
+
+    /// Removes the greatest value of the set and returns it, or `None` if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let mut rb: RoaringTreemap = (1..4).collect();
+    /// assert_eq!(rb.pop_max(), Some(3));
+    /// assert_eq!(rb.pop_max(), Some(2));
+    /// assert!(rb.iter().eq([1]));
+    /// ```
+    pub fn pop_max(&mut self) -> Option<u64> {
+        // The empty bitmaps are dropped until one of them yields a value
+        loop {
+            let (&key, bitmap) = self.map.iter_mut().next_back()?;
+            let value = bitmap.pop_max();
+            if bitmap.is_empty() {
+                self.map.remove(&key);
+            }
+            if let Some(value) = value {
+                return Some(util::join(key, value));
+            }
+        }
+    }
+
+    /// Removes the `n` smallest values of the set.
+    /// Returns the number of removed values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let mut rb: RoaringTreemap = (1..10).collect();
+    /// assert_eq!(rb.remove_smallest(3), 3);
+    /// assert!(rb.iter().eq(4..10));
+    /// assert_eq!(rb.remove_smallest(10), 6);
+    /// assert!(rb.is_empty());
+    /// ```
+    pub fn remove_smallest(&mut self, n: u64) -> u64 {
+        let mut removed = 0;
+        while removed < n {
+            let (&key, bitmap) = match self.map.iter_mut().next() {
+                Some(entry) => entry,
+                None => break,
+            };
+            if removed + bitmap.len() <= n {
+                removed += bitmap.len();
+                self.map.remove(&key);
+            } else {
+                removed += bitmap.remove_smallest(n - removed);
+            }
+        }
+        removed
+    }
+
+    /// Removes the `n` greatest values of the set.
+    /// Returns the number of removed values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let mut rb: RoaringTreemap = (1..10).collect();
+    /// assert_eq!(rb.remove_biggest(3), 3);
+    /// assert!(rb.iter().eq(1..7));
+    /// assert_eq!(rb.remove_biggest(10), 6);
+    /// assert!(rb.is_empty());
+    /// ```
+    pub fn remove_biggest(&mut self, n: u64) -> u64 {
+        let mut removed = 0;
+        while removed < n {
+            let (&key, bitmap) = match self.map.iter_mut().next_back() {
+                Some(entry) => entry,
+                None => break,
+            };
+            if removed + bitmap.len() <= n {
+                removed += bitmap.len();
+                self.map.remove(&key);
+            } else {
+                removed += bitmap.remove_biggest(n - removed);
+            }
+        }
+        removed
+    }
+
+    /// Flips the values in the range: the values of the range that are in the set are
+    /// removed and the others are inserted. Only the bitmaps of the range are modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let mut rb: RoaringTreemap = (0..10).collect();
+    /// rb.flip_range(5..15);
+    /// assert!(rb.iter().eq((0..5).chain(10..15)));
+    /// ```
+    pub fn flip_range<R>(&mut self, range: R)
+    where
+        R: RangeBounds<u64>,
+    {
+        let (start, end) = match util::convert_range_to_inclusive(range) {
+            Some(range) => (*range.start(), *range.end()),
+            None => return,
+        };
+
+        let (start_container_key, start_index) = util::split(start);
+        let (end_container_key, end_index) = util::split(end);
+
+        for key in start_container_key..=end_container_key {
+            let a = if key == start_container_key { start_index } else { 0 };
+            let b = if key == end_container_key { end_index } else { u32::MAX };
+            match self.map.entry(key) {
+                Entry::Vacant(ent) => {
+                    let mut rb = RoaringBitmap::new();
+                    rb.insert_range(a..=b);
+                    ent.insert(rb);
+                }
+                Entry::Occupied(mut ent) => {
+                    ent.get_mut().flip_range(a..=b);
+                    if ent.get().is_empty() {
+                        ent.remove();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of the set with the values in the range flipped,
+    /// see [`RoaringTreemap::flip_range`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb: RoaringTreemap = (0..10).collect();
+    /// assert!(rb.flipped(5..15).iter().eq((0..5).chain(10..15)));
+    /// ```
+    pub fn flipped<R>(&self, range: R) -> RoaringTreemap
+    where
+        R: RangeBounds<u64>,
+    {
+        let mut flipped = self.clone();
+        flipped.flip_range(range);
+        flipped
+    }
+
+    /// Unions in-place with the complement of `other` in the universe `0..universe_end`:
+    /// the values lower than `universe_end` that are not in `other` are inserted in the set.
+    /// The values of the set that are outside of the universe are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let mut rb: RoaringTreemap = [1, u64::MAX].into_iter().collect();
+    /// let other: RoaringTreemap = (2..8).collect();
+    ///
+    /// rb.or_not(&other, 10);
+    /// assert!(rb.iter().eq([0, 1, 8, 9, u64::MAX]));
+    /// ```
+    pub fn or_not(&mut self, other: &RoaringTreemap, universe_end: u64) {
+        if universe_end == 0 {
+            return;
+        }
+
+        let (end_container_key, _) = util::split(universe_end - 1);
+        let map = other.map.range(..=end_container_key).map(|(&k, rb)| (k, rb.clone())).collect();
+        let mut complement = RoaringTreemap { map };
+        complement.remove_range(universe_end..);
+        complement.flip_range(..universe_end);
+        *self |= complement;
+    }
+
+    /// Returns a copy of the set with `offset` added to every value,
+    /// the values that overflow or underflow are dropped.
+    ///
+    /// When the offset is a multiple of 2<sup>32</sup> only the keys of the bitmaps are changed,
+    /// otherwise the values of each bitmap are split between two neighbouring bitmaps.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb: RoaringTreemap = [0, 10, u32::MAX as u64, u64::MAX].into_iter().collect();
+    ///
+    /// assert!(rb.add_offset(100).iter().eq([100, 110, u32::MAX as u64 + 100]));
+    /// assert!(rb.add_offset(-10).iter().eq([0, u32::MAX as u64 - 10, u64::MAX - 10]));
+    /// ```
+    pub fn add_offset(&self, offset: i64) -> RoaringTreemap {
+        let key_offset = offset.div_euclid(1 << 32);
+        let offset = offset.rem_euclid(1 << 32);
+
+        let mut map = BTreeMap::new();
+        for (&key, bitmap) in &self.map {
+            let key = i64::from(key) + key_offset;
+            let (low, high) = if offset == 0 {
+                (bitmap.clone(), RoaringBitmap::new())
+            } else {
+                (bitmap.add_offset(offset), bitmap.add_offset(offset - (1 << 32)))
+            };
+            for (key, bitmap) in [(key, low), (key + 1, high)] {
+                if key < 0 || key > i64::from(u32::MAX) || bitmap.is_empty() {
+                    continue;
+                }
+                // The overflow of a bitmap goes to the same key as the next bitmap
+                match map.entry(key as u32) {
+                    Entry::Vacant(ent) => {
+                        ent.insert(bitmap);
+                    }
+                    Entry::Occupied(mut ent) => *ent.get_mut() |= bitmap,
+                }
+            }
+        }
+        RoaringTreemap { map }
+    }
+
     /// Returns `true` if this set contains the specified integer.
     ///
     /// # Examples
@@ -358,6 +608,104 @@ impl RoaringTreemap {
             .map(|(k, rb)| util::join(*k, rb.max().unwrap()))
     }
 
+    /// Returns the smallest value in the set that is greater than or equal to `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb: RoaringTreemap = [3, u64::MAX].into_iter().collect();
+    ///
+    /// assert_eq!(rb.next_value(3), Some(3));
+    /// assert_eq!(rb.next_value(4), Some(u64::MAX));
+    /// ```
+    pub fn next_value(&self, value: u64) -> Option<u64> {
+        let (hi, lo) = util::split(value);
+        self.map.range(hi..).find_map(|(&key, rb)| {
+            let next = if key == hi { rb.next_value(lo) } else { rb.min() };
+            next.map(|next| util::join(key, next))
+        })
+    }
+
+    /// Returns the greatest value in the set that is lower than or equal to `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb: RoaringTreemap = [3, u64::MAX].into_iter().collect();
+    ///
+    /// assert_eq!(rb.previous_value(u64::MAX - 1), Some(3));
+    /// assert_eq!(rb.previous_value(2), None);
+    /// ```
+    pub fn previous_value(&self, value: u64) -> Option<u64> {
+        let (hi, lo) = util::split(value);
+        self.map.range(..=hi).rev().find_map(|(&key, rb)| {
+            let previous = if key == hi { rb.previous_value(lo) } else { rb.max() };
+            previous.map(|previous| util::join(key, previous))
+        })
+    }
+
+    /// Returns the smallest value that is greater than or equal to `value`
+    /// and that is not in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb: RoaringTreemap = (0..100_000).collect();
+    ///
+    /// assert_eq!(rb.next_absent(5), Some(100_000));
+    /// assert_eq!(rb.next_absent(100_001), Some(100_001));
+    /// ```
+    pub fn next_absent(&self, value: u64) -> Option<u64> {
+        let (mut hi, mut lo) = util::split(value);
+        // Skip the bitmaps that are full from `lo` to the end
+        for (&key, rb) in self.map.range(hi..) {
+            if key != hi {
+                break;
+            }
+            if let Some(next) = rb.next_absent(lo) {
+                return Some(util::join(hi, next));
+            }
+            hi = hi.checked_add(1)?;
+            lo = 0;
+        }
+        Some(util::join(hi, lo))
+    }
+
+    /// Returns the greatest value that is lower than or equal to `value`
+    /// and that is not in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb: RoaringTreemap = (100_000..200_000).collect();
+    ///
+    /// assert_eq!(rb.previous_absent(150_000), Some(99_999));
+    /// assert_eq!(rb.previous_absent(99_999), Some(99_999));
+    /// ```
+    pub fn previous_absent(&self, value: u64) -> Option<u64> {
+        let (mut hi, mut lo) = util::split(value);
+        // Skip the bitmaps that are full from the start to `lo`
+        for (&key, rb) in self.map.range(..=hi).rev() {
+            if key != hi {
+                break;
+            }
+            if let Some(previous) = rb.previous_absent(lo) {
+                return Some(util::join(hi, previous));
+            }
+            hi = hi.checked_sub(1)?;
+            lo = u32::MAX;
+        }
+        Some(util::join(hi, lo))
+    }
+
     /// Returns the number of integers that are <= value. rank(u64::MAX) == len()
     ///
     /// # Examples
@@ -413,6 +761,46 @@ impl RoaringTreemap {
 
         None
     }
+
+    /// Returns the number of bytes allocated on the heap by the inner bitmaps,
+    /// plus the size of the entries of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let mut rb: RoaringTreemap = (0..4000).collect();
+    /// let usage = rb.memory_usage();
+    /// assert!(usage >= 4000 * 2);
+    ///
+    /// rb.remove_range(10..);
+    /// assert_eq!(rb.memory_usage(), usage);
+    /// rb.shrink_to_fit();
+    /// assert!(rb.memory_usage() < usage);
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        let entries = self.map.len() * mem::size_of::<(u32, RoaringBitmap)>();
+        entries + self.map.values().map(RoaringBitmap::memory_usage).sum::<usize>()
+    }
+
+    /// Releases the spare capacity of the inner bitmaps.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let mut rb: RoaringTreemap = (0..4000).collect();
+    /// rb.remove_range(10..);
+    /// rb.shrink_to_fit();
+    /// assert!(rb.iter().eq(0..10));
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        for bitmap in self.map.values_mut() {
+            bitmap.shrink_to_fit();
+        }
+    }
 }
 
 impl Default for RoaringTreemap {
@@ -1,10 +1,12 @@
 use std::collections::btree_map;
 use std::collections::BTreeMap;
 use std::iter::{self, FromIterator};
+use std::ops::{self, RangeBounds, RangeInclusive};
 
 use super::util;
 use crate::bitmap::IntoIter as IntoIter32;
 use crate::bitmap::Iter as Iter32;
+use crate::bitmap::Ranges as Ranges32;
 use crate::{NonSortedIntegers, RoaringBitmap, RoaringTreemap};
 
 struct To64Iter<'a> {
@@ -12,6 +14,47 @@ struct To64Iter<'a> {
     inner: Iter32<'a>,
 }
 
+impl To64Iter<'_> {
+    fn remaining(&self) -> u64 {
+        self.inner.size_hint().0 as u64
+    }
+
+    fn advance_to(&mut self, lo: u32) -> u64 {
+        let before = self.remaining();
+        self.inner.advance_to(lo);
+        before - self.remaining()
+    }
+
+    fn advance_back_to(&mut self, lo: u32) -> u64 {
+        let before = self.remaining();
+        self.inner.advance_back_to(lo);
+        before - self.remaining()
+    }
+
+    fn next_many(&mut self, buf: &mut [u64]) -> usize {
+        next_many(self.hi, buf, |buf| self.inner.next_many(buf))
+    }
+}
+
+/// Fills `buf` with the values returned by `next_many` joined with `hi`,
+/// using a temporary buffer of `u32`s.
+fn next_many(hi: u32, buf: &mut [u64], mut next_many: impl FnMut(&mut [u32]) -> usize) -> usize {
+    let mut values = [0; 256];
+    let mut n = 0;
+    while n < buf.len() {
+        let len = (buf.len() - n).min(values.len());
+        let written = next_many(&mut values[..len]);
+        for (slot, &value) in buf[n..].iter_mut().zip(&values[..written]) {
+            *slot = util::join(hi, value);
+        }
+        n += written;
+        if written < len {
+            break;
+        }
+    }
+    n
+}
+
 impl<'a> Iterator for To64Iter<'a> {
     type Item = u64;
     fn next(&mut self) -> Option<u64> {
@@ -25,10 +68,36 @@ impl DoubleEndedIterator for To64Iter<'_> {
     }
 }
 
-fn to64iter<'a>(t: (&'a u32, &'a RoaringBitmap)) -> To64Iter<'a> {
-    To64Iter { hi: *t.0, inner: t.1.iter() }
+struct To64Ranges<'a> {
+    hi: u32,
+    inner: Ranges32<'a>,
+}
+
+impl Iterator for To64Ranges<'_> {
+    type Item = RangeInclusive<u64>;
+    fn next(&mut self) -> Option<RangeInclusive<u64>> {
+        self.inner.next().map(|r| util::join(self.hi, *r.start())..=util::join(self.hi, *r.end()))
+    }
+}
+
+impl DoubleEndedIterator for To64Ranges<'_> {
+    fn next_back(&mut self) -> Option<RangeInclusive<u64>> {
+        self.inner
+            .next_back()
+            .map(|r| util::join(self.hi, *r.start())..=util::join(self.hi, *r.end()))
+    }
+}
+
+fn to64ranges<'a>(t: (&'a u32, &'a RoaringBitmap)) -> To64Ranges<'a> {
+    To64Ranges { hi: *t.0, inner: t.1.iter_ranges() }
 }
 
+type InnerRanges<'a> = iter::FlatMap<
+    btree_map::Iter<'a, u32, RoaringBitmap>,
+    To64Ranges<'a>,
+    fn((&'a u32, &'a RoaringBitmap)) -> To64Ranges<'a>,
+>;
+
 struct To64IntoIter {
     hi: u32,
     inner: IntoIter32,
@@ -47,46 +116,296 @@ impl DoubleEndedIterator for To64IntoIter {
     }
 }
 
+impl To64IntoIter {
+    fn next_many(&mut self, buf: &mut [u64]) -> usize {
+        next_many(self.hi, buf, |buf| self.inner.next_many(buf))
+    }
+}
+
 fn to64intoiter(t: (u32, RoaringBitmap)) -> To64IntoIter {
     To64IntoIter { hi: t.0, inner: t.1.into_iter() }
 }
 
-type InnerIter<'a> = iter::FlatMap<
-    btree_map::Iter<'a, u32, RoaringBitmap>,
-    To64Iter<'a>,
-    fn((&'a u32, &'a RoaringBitmap)) -> To64Iter<'a>,
->;
-type InnerIntoIter = iter::FlatMap<
-    btree_map::IntoIter<u32, RoaringBitmap>,
-    To64IntoIter,
-    fn((u32, RoaringBitmap)) -> To64IntoIter,
->;
-
 /// An iterator for `RoaringTreemap`.
 pub struct Iter<'a> {
-    inner: InnerIter<'a>,
+    map: &'a BTreeMap<u32, RoaringBitmap>,
+    front: Option<To64Iter<'a>>,
+    // The keys of the bitmaps between the front and the back iterators
+    keys: ops::Range<u64>,
+    back: Option<To64Iter<'a>>,
     size_hint: u64,
 }
 
+/// An iterator over the values of a `RoaringTreemap` that are within a range.
+pub struct Range<'a> {
+    inner: Iter<'a>,
+}
+
+/// An iterator over the maximal runs of consecutive values of a `RoaringTreemap`.
+pub struct Ranges<'a> {
+    bitmaps: InnerRanges<'a>,
+    // The ranges taken from the bitmaps that may be merged with the following ones
+    front: Option<RangeInclusive<u64>>,
+    back: Option<RangeInclusive<u64>>,
+}
+
 /// An iterator for `RoaringTreemap`.
 pub struct IntoIter {
-    inner: InnerIntoIter,
+    front: Option<To64IntoIter>,
+    bitmaps: btree_map::IntoIter<u32, RoaringBitmap>,
+    back: Option<To64IntoIter>,
     size_hint: u64,
 }
 
 impl<'a> Iter<'a> {
-    fn new(map: &BTreeMap<u32, RoaringBitmap>) -> Iter {
-        let size_hint: u64 = map.iter().map(|(_, r)| r.len()).sum();
-        let i = map.iter().flat_map(to64iter as _);
-        Iter { inner: i, size_hint }
+    fn new(map: &BTreeMap<u32, RoaringBitmap>) -> Iter<'_> {
+        let size_hint: u64 = map.values().map(|r| r.len()).sum();
+        Iter { map, front: None, keys: 0..1 << 32, back: None, size_hint }
+    }
+
+    /// The bitmaps that are between the front and the back iterators.
+    fn bitmaps(&self) -> btree_map::Range<'a, u32, RoaringBitmap> {
+        if self.keys.start < self.keys.end {
+            self.map.range(self.keys.start as u32..=(self.keys.end - 1) as u32)
+        } else {
+            self.map.range(..0)
+        }
+    }
+
+    /// Writes the next values of the iterator into `buf` and returns the number of
+    /// written values, which is lower than the length of `buf` only when the iterator
+    /// is exhausted. The values are copied a container at a time, which is faster than
+    /// calling `next` for each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let treemap: RoaringTreemap = (0..3).chain(1 << 32..(1 << 32) + 3).collect();
+    /// let mut iter = treemap.iter();
+    /// let mut buf = [0; 4];
+    ///
+    /// assert_eq!(iter.next(), Some(0));
+    /// assert_eq!(iter.next_many(&mut buf), 4);
+    /// assert_eq!(buf, [1, 2, 1 << 32, (1 << 32) + 1]);
+    /// assert_eq!(iter.next_many(&mut buf), 1);
+    /// ```
+    pub fn next_many(&mut self, buf: &mut [u64]) -> usize {
+        let mut n = 0;
+        loop {
+            if let Some(front) = &mut self.front {
+                n += front.next_many(&mut buf[n..]);
+                if n == buf.len() {
+                    break;
+                }
+            }
+            match self.bitmaps().next() {
+                Some((&hi, bitmap)) => {
+                    self.keys.start = u64::from(hi) + 1;
+                    self.front = Some(To64Iter { hi, inner: bitmap.iter() });
+                }
+                None => {
+                    n += self.back.as_mut().map_or(0, |back| back.next_many(&mut buf[n..]));
+                    break;
+                }
+            }
+        }
+        self.size_hint = self.size_hint.saturating_sub(n as u64);
+        n
+    }
+
+    /// Advance the iterator to the first position where the item is greater than or equal
+    /// to `n`. The values before `n` are skipped without being iterated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let treemap: RoaringTreemap = [1, 2, 3, u64::MAX - 1, u64::MAX].into_iter().collect();
+    /// let mut iter = treemap.iter();
+    ///
+    /// iter.advance_to(3);
+    /// assert_eq!(iter.next(), Some(3));
+    /// iter.advance_to(1 << 40);
+    /// assert_eq!(iter.next(), Some(u64::MAX - 1));
+    /// ```
+    pub fn advance_to(&mut self, n: u64) {
+        let (hi, lo) = util::split(n);
+        let mut skipped = 0;
+        if let Some(front) = &mut self.front {
+            if front.hi >= hi {
+                if front.hi == hi {
+                    skipped = front.advance_to(lo);
+                }
+                self.size_hint = self.size_hint.saturating_sub(skipped);
+                return;
+            }
+            skipped += front.remaining();
+            self.front = None;
+        }
+
+        let hi = u64::from(hi);
+        skipped += self
+            .bitmaps()
+            .take_while(|&(&key, _)| u64::from(key) < hi)
+            .map(|(_, bitmap)| bitmap.len())
+            .sum::<u64>();
+        self.keys.start = self.keys.start.max(hi);
+        match self.bitmaps().next() {
+            Some((&key, bitmap)) if u64::from(key) == hi => {
+                self.keys.start = hi + 1;
+                let mut front = To64Iter { hi: key, inner: bitmap.iter() };
+                skipped += front.advance_to(lo);
+                self.front = Some(front);
+            }
+            Some(_) => (),
+            None => match &mut self.back {
+                Some(back) if u64::from(back.hi) < hi => {
+                    skipped += back.remaining();
+                    self.back = None;
+                }
+                Some(back) if u64::from(back.hi) == hi => skipped += back.advance_to(lo),
+                _ => (),
+            },
+        }
+        self.size_hint = self.size_hint.saturating_sub(skipped);
+    }
+
+    /// Advance the back of the iterator to the last position where the item is lower than
+    /// or equal to `n`. The values after `n` are skipped without being iterated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let treemap: RoaringTreemap = [1, 2, 3, u64::MAX - 1, u64::MAX].into_iter().collect();
+    /// let mut iter = treemap.iter();
+    ///
+    /// iter.advance_back_to(u64::MAX - 1);
+    /// assert_eq!(iter.next_back(), Some(u64::MAX - 1));
+    /// iter.advance_back_to(1 << 40);
+    /// assert_eq!(iter.next_back(), Some(3));
+    /// ```
+    pub fn advance_back_to(&mut self, n: u64) {
+        let (hi, lo) = util::split(n);
+        let mut skipped = 0;
+        if let Some(back) = &mut self.back {
+            if back.hi <= hi {
+                if back.hi == hi {
+                    skipped = back.advance_back_to(lo);
+                }
+                self.size_hint = self.size_hint.saturating_sub(skipped);
+                return;
+            }
+            skipped += back.remaining();
+            self.back = None;
+        }
+
+        let hi = u64::from(hi);
+        skipped += self
+            .bitmaps()
+            .rev()
+            .take_while(|&(&key, _)| u64::from(key) > hi)
+            .map(|(_, bitmap)| bitmap.len())
+            .sum::<u64>();
+        self.keys.end = self.keys.end.min(hi + 1);
+        match self.bitmaps().next_back() {
+            Some((&key, bitmap)) if u64::from(key) == hi => {
+                self.keys.end = hi;
+                let mut back = To64Iter { hi: key, inner: bitmap.iter() };
+                skipped += back.advance_back_to(lo);
+                self.back = Some(back);
+            }
+            Some(_) => (),
+            None => match &mut self.front {
+                Some(front) if u64::from(front.hi) > hi => {
+                    skipped += front.remaining();
+                    self.front = None;
+                }
+                Some(front) if u64::from(front.hi) == hi => skipped += front.advance_back_to(lo),
+                _ => (),
+            },
+        }
+        self.size_hint = self.size_hint.saturating_sub(skipped);
+    }
+}
+
+impl<'a> Range<'a> {
+    fn new<R: RangeBounds<u64>>(map: &'a BTreeMap<u32, RoaringBitmap>, range: R) -> Range<'a> {
+        let (start, end) = match util::convert_range_to_inclusive(range) {
+            Some(range) => (*range.start(), *range.end()),
+            None => {
+                return Range {
+                    inner: Iter { map, front: None, keys: 0..0, back: None, size_hint: 0 },
+                }
+            }
+        };
+        let (start_hi, _) = util::split(start);
+        let (end_hi, _) = util::split(end);
+        let size_hint = map.range(start_hi..=end_hi).map(|(_, bitmap)| bitmap.len()).sum();
+        let keys = u64::from(start_hi)..u64::from(end_hi) + 1;
+
+        let mut inner = Iter { map, front: None, keys, back: None, size_hint };
+        inner.advance_to(start);
+        inner.advance_back_to(end);
+        Range { inner }
+    }
+}
+
+impl Ranges<'_> {
+    fn new(map: &BTreeMap<u32, RoaringBitmap>) -> Ranges<'_> {
+        let bitmaps = map.iter().flat_map(to64ranges as _);
+        Ranges { bitmaps, front: None, back: None }
     }
 }
 
 impl IntoIter {
     fn new(map: BTreeMap<u32, RoaringBitmap>) -> IntoIter {
         let size_hint = map.values().map(|r| r.len()).sum();
-        let i = map.into_iter().flat_map(to64intoiter as _);
-        IntoIter { inner: i, size_hint }
+        IntoIter { front: None, bitmaps: map.into_iter(), back: None, size_hint }
+    }
+
+    /// Writes the next values of the iterator into `buf` and returns the number of
+    /// written values, which is lower than the length of `buf` only when the iterator
+    /// is exhausted. The values are copied a container at a time, which is faster than
+    /// calling `next` for each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let treemap: RoaringTreemap = (0..3).chain(1 << 32..(1 << 32) + 3).collect();
+    /// let mut iter = treemap.into_iter();
+    /// let mut buf = [0; 4];
+    ///
+    /// assert_eq!(iter.next(), Some(0));
+    /// assert_eq!(iter.next_many(&mut buf), 4);
+    /// assert_eq!(buf, [1, 2, 1 << 32, (1 << 32) + 1]);
+    /// assert_eq!(iter.next_many(&mut buf), 1);
+    /// ```
+    pub fn next_many(&mut self, buf: &mut [u64]) -> usize {
+        let mut n = 0;
+        loop {
+            if let Some(front) = &mut self.front {
+                n += front.next_many(&mut buf[n..]);
+                if n == buf.len() {
+                    break;
+                }
+            }
+            match self.bitmaps.next() {
+                Some(bitmap) => self.front = Some(to64intoiter(bitmap)),
+                None => {
+                    n += self.back.as_mut().map_or(0, |back| back.next_many(&mut buf[n..]));
+                    break;
+                }
+            }
+        }
+        self.size_hint = self.size_hint.saturating_sub(n as u64);
+        n
     }
 }
 
@@ -95,7 +414,18 @@ impl<'a> Iterator for Iter<'a> {
 
     fn next(&mut self) -> Option<u64> {
         self.size_hint = self.size_hint.saturating_sub(1);
-        self.inner.next()
+        loop {
+            if let Some(value) = self.front.as_mut().and_then(Iterator::next) {
+                return Some(value);
+            }
+            match self.bitmaps().next() {
+                Some((&hi, bitmap)) => {
+                    self.keys.start = u64::from(hi) + 1;
+                    self.front = Some(To64Iter { hi, inner: bitmap.iter() });
+                }
+                None => return self.back.as_mut().and_then(Iterator::next),
+            }
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -110,7 +440,18 @@ impl<'a> Iterator for Iter<'a> {
 impl DoubleEndedIterator for Iter<'_> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.size_hint = self.size_hint.saturating_sub(1);
-        self.inner.next_back()
+        loop {
+            if let Some(value) = self.back.as_mut().and_then(DoubleEndedIterator::next_back) {
+                return Some(value);
+            }
+            match self.bitmaps().next_back() {
+                Some((&hi, bitmap)) => {
+                    self.keys.end = u64::from(hi);
+                    self.back = Some(To64Iter { hi, inner: bitmap.iter() });
+                }
+                None => return self.front.as_mut().and_then(DoubleEndedIterator::next_back),
+            }
+        }
     }
 }
 
@@ -121,12 +462,91 @@ impl ExactSizeIterator for Iter<'_> {
     }
 }
 
+impl Iterator for Range<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Range<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for Range<'_> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl Iterator for Ranges<'_> {
+    type Item = RangeInclusive<u64>;
+
+    fn next(&mut self) -> Option<RangeInclusive<u64>> {
+        let (start, mut end) = match self.front.take().or_else(|| self.bitmaps.next()) {
+            Some(range) => range.into_inner(),
+            None => return self.back.take(),
+        };
+        for next in self.bitmaps.by_ref() {
+            if end + 1 != *next.start() {
+                self.front = Some(next);
+                return Some(start..=end);
+            }
+            end = *next.end();
+        }
+        // The bitmaps are exhausted but the range may continue in the back one
+        match self.back.take() {
+            Some(back) if end + 1 == *back.start() => end = *back.end(),
+            back => self.back = back,
+        }
+        Some(start..=end)
+    }
+}
+
+impl DoubleEndedIterator for Ranges<'_> {
+    fn next_back(&mut self) -> Option<RangeInclusive<u64>> {
+        let (mut start, end) = match self.back.take().or_else(|| self.bitmaps.next_back()) {
+            Some(range) => range.into_inner(),
+            None => return self.front.take(),
+        };
+        for prev in self.bitmaps.by_ref().rev() {
+            if *prev.end() + 1 != start {
+                self.back = Some(prev);
+                return Some(start..=end);
+            }
+            start = *prev.start();
+        }
+        // The bitmaps are exhausted but the range may continue in the front one
+        match self.front.take() {
+            Some(front) if *front.end() + 1 == start => start = *front.start(),
+            front => self.front = front,
+        }
+        Some(start..=end)
+    }
+}
+
 impl Iterator for IntoIter {
     type Item = u64;
 
     fn next(&mut self) -> Option<u64> {
         self.size_hint = self.size_hint.saturating_sub(1);
-        self.inner.next()
+        loop {
+            if let Some(value) = self.front.as_mut().and_then(Iterator::next) {
+                return Some(value);
+            }
+            match self.bitmaps.next() {
+                Some(bitmap) => self.front = Some(to64intoiter(bitmap)),
+                None => return self.back.as_mut().and_then(Iterator::next),
+            }
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -141,7 +561,15 @@ impl Iterator for IntoIter {
 impl DoubleEndedIterator for IntoIter {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.size_hint = self.size_hint.saturating_sub(1);
-        self.inner.next_back()
+        loop {
+            if let Some(value) = self.back.as_mut().and_then(DoubleEndedIterator::next_back) {
+                return Some(value);
+            }
+            match self.bitmaps.next_back() {
+                Some(bitmap) => self.back = Some(to64intoiter(bitmap)),
+                None => return self.front.as_mut().and_then(DoubleEndedIterator::next_back),
+            }
+        }
     }
 }
 
@@ -169,10 +597,52 @@ impl RoaringTreemap {
     /// assert_eq!(iter.next(), Some(2));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         Iter::new(&self.map)
     }
 
+    /// Iterator over the maximal runs of consecutive values stored in the RoaringTreemap,
+    /// guarantees ranges are ordered by value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let treemap: RoaringTreemap = (1..3).chain((1 << 32) - 5..(1 << 32) + 5).collect();
+    /// let mut ranges = treemap.iter_ranges();
+    ///
+    /// assert_eq!(ranges.next(), Some(1..=2));
+    /// assert_eq!(ranges.next(), Some((1 << 32) - 5..=(1 << 32) + 4));
+    /// assert_eq!(ranges.next(), None);
+    /// ```
+    pub fn iter_ranges(&self) -> Ranges<'_> {
+        Ranges::new(&self.map)
+    }
+
+    /// Iterator over the values stored in the RoaringTreemap that are within the given range,
+    /// guarantees values are ordered by value. The iterator starts directly at the first value
+    /// of the range, the values before it are not iterated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let treemap: RoaringTreemap = (1..10).chain(u64::MAX - 5..=u64::MAX).collect();
+    /// let mut range = treemap.range(5..u64::MAX);
+    ///
+    /// assert_eq!(range.len(), 10);
+    /// assert_eq!(range.next(), Some(5));
+    /// assert_eq!(range.next_back(), Some(u64::MAX - 1));
+    /// ```
+    pub fn range<R>(&self, range: R) -> Range<'_>
+    where
+        R: RangeBounds<u64>,
+    {
+        Range::new(&self.map, range)
+    }
+
     /// Iterator over pairs of partition number and the corresponding RoaringBitmap.
     /// The partition number is defined by the 32 most significant bits of the bit index.
     ///
@@ -188,7 +658,7 @@ impl RoaringTreemap {
     /// assert_eq!(bitmaps.next(), Some((0, &(0..6000).collect::<RoaringBitmap>())));
     /// assert_eq!(bitmaps.next(), None);
     /// ```
-    pub fn bitmaps(&self) -> BitmapIter {
+    pub fn bitmaps(&self) -> BitmapIter<'_> {
         BitmapIter(self.map.iter())
     }
 
@@ -232,7 +702,7 @@ impl IntoIterator for RoaringTreemap {
 
 impl<const N: usize> From<[u64; N]> for RoaringTreemap {
     fn from(arr: [u64; N]) -> Self {
-        RoaringTreemap::from_iter(arr.into_iter())
+        RoaringTreemap::from_iter(arr)
     }
 }
 
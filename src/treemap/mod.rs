@@ -15,8 +15,10 @@ mod ops;
 #[cfg(feature = "serde")]
 mod serde;
 mod serialization;
+mod statistics;
 
-pub use self::iter::{IntoIter, Iter};
+pub use self::iter::{IntoIter, Iter, Range, Ranges};
+pub use self::statistics::Statistics;
 
 /// A compressed bitmap with u64 values.
 /// Implemented as a `BTreeMap` of `RoaringBitmap`s.
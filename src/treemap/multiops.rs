@@ -1,7 +1,7 @@
 use std::{
     borrow::Borrow,
     cmp::Ordering,
-    collections::{binary_heap::PeekMut, BTreeMap, BinaryHeap},
+    collections::{binary_heap::PeekMut, btree_map, BTreeMap, BinaryHeap},
     mem,
 };
 
@@ -12,6 +12,7 @@ where
     I: IntoIterator<Item = RoaringTreemap>,
 {
     type Output = RoaringTreemap;
+    type LenOutput = u64;
 
     fn union(self) -> Self::Output {
         try_simple_multi_op_owned::<_, _, UnionOp>(
@@ -40,6 +41,38 @@ where
         )
         .unwrap()
     }
+
+    fn at_least(self, k: usize) -> Self::Output {
+        try_with_bitmaps_owned(
+            self.into_iter().map(Ok::<_, std::convert::Infallible>),
+            |bitmaps, _| multi_at_least(bitmaps, k),
+        )
+        .unwrap()
+    }
+
+    fn union_len(self) -> Self::LenOutput {
+        try_with_bitmaps_owned(
+            self.into_iter().map(Ok::<_, std::convert::Infallible>),
+            |bitmaps, _| multi_union_len(bitmaps),
+        )
+        .unwrap()
+    }
+
+    fn intersection_len(self) -> Self::LenOutput {
+        try_with_bitmaps_owned(
+            self.into_iter().map(Ok::<_, std::convert::Infallible>),
+            multi_intersection_len,
+        )
+        .unwrap()
+    }
+
+    fn symmetric_difference_len(self) -> Self::LenOutput {
+        try_with_bitmaps_owned(
+            self.into_iter().map(Ok::<_, std::convert::Infallible>),
+            |bitmaps, _| multi_symmetric_difference_len(bitmaps),
+        )
+        .unwrap()
+    }
 }
 
 impl<I, E> MultiOps<Result<RoaringTreemap, E>> for I
@@ -47,6 +80,7 @@ where
     I: IntoIterator<Item = Result<RoaringTreemap, E>>,
 {
     type Output = Result<RoaringTreemap, E>;
+    type LenOutput = Result<u64, E>;
 
     fn union(self) -> Self::Output {
         try_simple_multi_op_owned::<_, _, UnionOp>(self)
@@ -63,6 +97,22 @@ where
     fn symmetric_difference(self) -> Self::Output {
         try_simple_multi_op_owned::<_, _, SymmetricDifferenceOp>(self)
     }
+
+    fn at_least(self, k: usize) -> Self::Output {
+        try_with_bitmaps_owned(self, |bitmaps, _| multi_at_least(bitmaps, k))
+    }
+
+    fn union_len(self) -> Self::LenOutput {
+        try_with_bitmaps_owned(self, |bitmaps, _| multi_union_len(bitmaps))
+    }
+
+    fn intersection_len(self) -> Self::LenOutput {
+        try_with_bitmaps_owned(self, multi_intersection_len)
+    }
+
+    fn symmetric_difference_len(self) -> Self::LenOutput {
+        try_with_bitmaps_owned(self, |bitmaps, _| multi_symmetric_difference_len(bitmaps))
+    }
 }
 
 #[inline]
@@ -239,6 +289,153 @@ where
     Ok(RoaringTreemap { map })
 }
 
+/// Calls `f` with an iterator over the bitmaps of each treemap and the number of treemaps.
+///
+/// The treemaps are all pulled from the iterator first: the bitmaps of a key are only
+/// known once every treemap has been seen.
+#[inline]
+fn try_with_bitmaps_owned<E, I, R>(
+    treemaps: I,
+    f: impl FnOnce(Vec<btree_map::IntoIter<u32, RoaringBitmap>>, usize) -> R,
+) -> Result<R, E>
+where
+    I: IntoIterator<Item = Result<RoaringTreemap, E>>,
+{
+    let iters = treemaps
+        .into_iter()
+        .map(|treemap| treemap.map(|treemap| treemap.map.into_iter()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let count = iters.len();
+    Ok(f(iters, count))
+}
+
+#[inline]
+fn try_with_bitmaps_ref<'a, E: 'a, I, R>(
+    treemaps: I,
+    f: impl FnOnce(Vec<btree_map::Iter<'a, u32, RoaringBitmap>>, usize) -> R,
+) -> Result<R, E>
+where
+    I: IntoIterator<Item = Result<&'a RoaringTreemap, E>>,
+{
+    let iters = treemaps
+        .into_iter()
+        .map(|treemap| treemap.map(|treemap| treemap.map.iter()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let count = iters.len();
+    Ok(f(iters, count))
+}
+
+/// Calls `f` with each key and its bitmaps, in increasing key order.
+///
+/// The sorted bitmaps of the treemaps are merged as they are iterated,
+/// only the bitmaps of the current key are gathered at a time.
+fn for_each_key<K, R, I>(iters: Vec<I>, mut f: impl FnMut(u32, &[R]))
+where
+    K: Borrow<u32>,
+    R: Borrow<RoaringBitmap>,
+    I: Iterator<Item = (K, R)>,
+{
+    let mut heap: BinaryHeap<_> = iters
+        .into_iter()
+        .filter_map(|mut iter| {
+            iter.next().map(|(key, bitmap)| PeekedRoaringBitmap {
+                key: *key.borrow(),
+                bitmap,
+                iter,
+            })
+        })
+        .collect();
+
+    let mut bitmaps = Vec::new();
+    let mut current_key = 0;
+
+    while let Some(mut peek) = heap.peek_mut() {
+        let (key, bitmap) = match peek.iter.next() {
+            Some((next_key, next_bitmap)) => {
+                let key = peek.key;
+                peek.key = *next_key.borrow();
+                let bitmap = mem::replace(&mut peek.bitmap, next_bitmap);
+                (key, bitmap)
+            }
+            None => {
+                let poped = PeekMut::pop(peek);
+                (poped.key, poped.bitmap)
+            }
+        };
+
+        if !bitmaps.is_empty() && current_key != key {
+            f(current_key, &bitmaps);
+            bitmaps.clear();
+        }
+
+        current_key = key;
+        bitmaps.push(bitmap);
+    }
+
+    if !bitmaps.is_empty() {
+        f(current_key, &bitmaps);
+    }
+}
+
+fn multi_at_least<K, R, I>(iters: Vec<I>, k: usize) -> RoaringTreemap
+where
+    K: Borrow<u32>,
+    R: Borrow<RoaringBitmap>,
+    I: Iterator<Item = (K, R)>,
+{
+    // Only the keys present in at least k treemaps are computed
+    let mut map = BTreeMap::new();
+    for_each_key(iters, |key, bitmaps| {
+        if bitmaps.len() >= k {
+            let computed_bitmap = bitmaps.iter().map(Borrow::borrow).at_least(k);
+            if !computed_bitmap.is_empty() {
+                map.insert(key, computed_bitmap);
+            }
+        }
+    });
+    RoaringTreemap { map }
+}
+
+fn multi_union_len<K, R, I>(iters: Vec<I>) -> u64
+where
+    K: Borrow<u32>,
+    R: Borrow<RoaringBitmap>,
+    I: Iterator<Item = (K, R)>,
+{
+    let mut len = 0;
+    for_each_key(iters, |_, bitmaps| len += bitmaps.iter().map(Borrow::borrow).union_len());
+    len
+}
+
+fn multi_intersection_len<K, R, I>(iters: Vec<I>, count: usize) -> u64
+where
+    K: Borrow<u32>,
+    R: Borrow<RoaringBitmap>,
+    I: Iterator<Item = (K, R)>,
+{
+    let mut len = 0;
+    for_each_key(iters, |_, bitmaps| {
+        // A key is in the intersection only if every treemap has a bitmap for it
+        if bitmaps.len() == count {
+            len += bitmaps.iter().map(Borrow::borrow).intersection_len();
+        }
+    });
+    len
+}
+
+fn multi_symmetric_difference_len<K, R, I>(iters: Vec<I>) -> u64
+where
+    K: Borrow<u32>,
+    R: Borrow<RoaringBitmap>,
+    I: Iterator<Item = (K, R)>,
+{
+    let mut len = 0;
+    for_each_key(iters, |_, bitmaps| {
+        len += bitmaps.iter().map(Borrow::borrow).symmetric_difference_len()
+    });
+    len
+}
+
 trait Op {
     fn op_owned<I: IntoIterator<Item = RoaringBitmap>>(iter: I) -> RoaringBitmap;
     fn op_ref<'a, I: IntoIterator<Item = &'a RoaringBitmap>>(iter: I) -> RoaringBitmap;
@@ -297,6 +494,7 @@ where
     I: IntoIterator<Item = &'a RoaringTreemap>,
 {
     type Output = RoaringTreemap;
+    type LenOutput = u64;
 
     fn union(self) -> Self::Output {
         try_simple_multi_op_ref::<_, _, UnionOp>(
@@ -325,6 +523,38 @@ where
         )
         .unwrap()
     }
+
+    fn at_least(self, k: usize) -> Self::Output {
+        try_with_bitmaps_ref(
+            self.into_iter().map(Ok::<_, std::convert::Infallible>),
+            |bitmaps, _| multi_at_least(bitmaps, k),
+        )
+        .unwrap()
+    }
+
+    fn union_len(self) -> Self::LenOutput {
+        try_with_bitmaps_ref(
+            self.into_iter().map(Ok::<_, std::convert::Infallible>),
+            |bitmaps, _| multi_union_len(bitmaps),
+        )
+        .unwrap()
+    }
+
+    fn intersection_len(self) -> Self::LenOutput {
+        try_with_bitmaps_ref(
+            self.into_iter().map(Ok::<_, std::convert::Infallible>),
+            multi_intersection_len,
+        )
+        .unwrap()
+    }
+
+    fn symmetric_difference_len(self) -> Self::LenOutput {
+        try_with_bitmaps_ref(
+            self.into_iter().map(Ok::<_, std::convert::Infallible>),
+            |bitmaps, _| multi_symmetric_difference_len(bitmaps),
+        )
+        .unwrap()
+    }
 }
 
 impl<'a, I, E: 'a> MultiOps<Result<&'a RoaringTreemap, E>> for I
@@ -332,6 +562,7 @@ where
     I: IntoIterator<Item = Result<&'a RoaringTreemap, E>>,
 {
     type Output = Result<RoaringTreemap, E>;
+    type LenOutput = Result<u64, E>;
 
     fn union(self) -> Self::Output {
         try_simple_multi_op_ref::<_, _, UnionOp>(self)
@@ -348,6 +579,22 @@ where
     fn symmetric_difference(self) -> Self::Output {
         try_simple_multi_op_ref::<_, _, SymmetricDifferenceOp>(self)
     }
+
+    fn at_least(self, k: usize) -> Self::Output {
+        try_with_bitmaps_ref(self, |bitmaps, _| multi_at_least(bitmaps, k))
+    }
+
+    fn union_len(self) -> Self::LenOutput {
+        try_with_bitmaps_ref(self, |bitmaps, _| multi_union_len(bitmaps))
+    }
+
+    fn intersection_len(self) -> Self::LenOutput {
+        try_with_bitmaps_ref(self, multi_intersection_len)
+    }
+
+    fn symmetric_difference_len(self) -> Self::LenOutput {
+        try_with_bitmaps_ref(self, |bitmaps, _| multi_symmetric_difference_len(bitmaps))
+    }
 }
 
 struct PeekedRoaringBitmap<R, I> {
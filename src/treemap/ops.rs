@@ -2,7 +2,7 @@ use std::collections::btree_map::Entry;
 use std::mem;
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
 
-use crate::RoaringTreemap;
+use crate::{RoaringBitmap, RoaringTreemap};
 
 impl RoaringTreemap {
     /// Computes the len of the union with the specified other treemap without creating a new
@@ -100,6 +100,135 @@ impl RoaringTreemap {
             .wrapping_sub(intersection_len)
             .wrapping_sub(intersection_len)
     }
+
+    /// Computes the Jaccard index between the two treemaps, the len of the intersection
+    /// divided by the len of the union, in a single pass over the containers.
+    ///
+    /// Returns `NaN` if both treemaps are empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb1: RoaringTreemap = (1..4).collect();
+    /// let rb2: RoaringTreemap = (3..5).collect();
+    ///
+    /// assert_eq!(rb1.jaccard_index(&rb2), 0.25);
+    /// ```
+    pub fn jaccard_index(&self, other: &RoaringTreemap) -> f64 {
+        let (lhs_len, rhs_len, intersection_len) = self.lens(other);
+        intersection_len as f64 / (lhs_len + rhs_len - intersection_len) as f64
+    }
+
+    /// Computes the overlap coefficient between the two treemaps, the len of the intersection
+    /// divided by the len of the smallest treemap, in a single pass over the containers.
+    ///
+    /// Returns `NaN` if one of the treemaps is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb1: RoaringTreemap = (1..4).collect();
+    /// let rb2: RoaringTreemap = (3..5).collect();
+    ///
+    /// assert_eq!(rb1.overlap_coefficient(&rb2), 0.5);
+    /// ```
+    pub fn overlap_coefficient(&self, other: &RoaringTreemap) -> f64 {
+        let (lhs_len, rhs_len, intersection_len) = self.lens(other);
+        intersection_len as f64 / lhs_len.min(rhs_len) as f64
+    }
+
+    /// Computes the Sørensen–Dice coefficient between the two treemaps, twice the len of the
+    /// intersection divided by the sum of the lens, in a single pass over the containers.
+    ///
+    /// Returns `NaN` if both treemaps are empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb1: RoaringTreemap = (1..4).collect();
+    /// let rb2: RoaringTreemap = (3..5).collect();
+    ///
+    /// assert_eq!(rb1.dice_coefficient(&rb2), 0.4);
+    /// ```
+    pub fn dice_coefficient(&self, other: &RoaringTreemap) -> f64 {
+        let (lhs_len, rhs_len, intersection_len) = self.lens(other);
+        2.0 * intersection_len as f64 / (lhs_len + rhs_len) as f64
+    }
+
+    /// Computes the Hamming distance between the two treemaps, the number of values that are in
+    /// only one of them, in a single pass over the containers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb1: RoaringTreemap = (1..4).collect();
+    /// let rb2: RoaringTreemap = (3..5).collect();
+    ///
+    /// assert_eq!(rb1.hamming_distance(&rb2), (rb1 ^ rb2).len());
+    /// ```
+    pub fn hamming_distance(&self, other: &RoaringTreemap) -> u64 {
+        let (lhs_len, rhs_len, intersection_len) = self.lens(other);
+        lhs_len + rhs_len - 2 * intersection_len
+    }
+
+    /// Computes the len of the intersection with each of the other treemaps,
+    /// walking the bitmaps of this treemap only once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let rb1: RoaringTreemap = (1..10).collect();
+    /// let rb2: RoaringTreemap = (3..5).collect();
+    /// let rb3: RoaringTreemap = (8..100).collect();
+    ///
+    /// assert_eq!(rb1.intersection_len_many(&[&rb2, &rb3]), vec![2, 2]);
+    /// ```
+    pub fn intersection_len_many(&self, others: &[&RoaringTreemap]) -> Vec<u64> {
+        let mut lens = vec![0; others.len()];
+
+        for (key, bitmap) in &self.map {
+            let (indices, inner): (Vec<usize>, Vec<&RoaringBitmap>) = others
+                .iter()
+                .enumerate()
+                .filter_map(|(i, other)| other.map.get(key).map(|rhs| (i, rhs)))
+                .unzip();
+            for (i, len) in indices.into_iter().zip(bitmap.intersection_len_many(&inner)) {
+                lens[i] += len;
+            }
+        }
+
+        lens
+    }
+
+    /// Returns the len of both treemaps and the len of their intersection,
+    /// computed in a single pass over the containers.
+    fn lens(&self, other: &RoaringTreemap) -> (u64, u64, u64) {
+        let mut lens = (0, 0, 0);
+        for pair in self.pairs(other) {
+            match pair {
+                (Some(lhs), None) => lens.0 += lhs.len(),
+                (None, Some(rhs)) => lens.1 += rhs.len(),
+                (Some(lhs), Some(rhs)) => {
+                    let (lhs_len, rhs_len, intersection_len) = lhs.lens(rhs);
+                    lens.0 += lhs_len;
+                    lens.1 += rhs_len;
+                    lens.2 += intersection_len;
+                }
+                (None, None) => (),
+            }
+        }
+        lens
+    }
 }
 
 impl BitOr<RoaringTreemap> for RoaringTreemap {
@@ -403,6 +532,7 @@ impl BitXorAssign<&RoaringTreemap> for RoaringTreemap {
 mod test {
     use crate::{MultiOps, RoaringTreemap};
     use proptest::prelude::*;
+    use std::convert::Infallible;
 
     // fast count tests
     proptest! {
@@ -537,5 +667,64 @@ mod test {
                 prop_assert_eq!(&ref_assign, roar);
             }
         }
+
+        #[test]
+        fn all_at_least_give_the_same_result(
+            a in RoaringTreemap::arbitrary(),
+            b in RoaringTreemap::arbitrary(),
+            c in RoaringTreemap::arbitrary()
+        ) {
+            let union = &a | &b | &c;
+            let intersection = &a & &b & &c;
+            let majority = (&a & &b) | (&a & &c) | (&b & &c);
+
+            for (k, expected) in [(0, &union), (1, &union), (2, &majority), (3, &intersection)] {
+                let ref_multiop = [&a, &b, &c].at_least(k);
+                let own_multiop = [a.clone(), b.clone(), c.clone()].at_least(k);
+
+                let ref_multiop_try = [&a, &b, &c].map(Ok::<_, Infallible>).at_least(k).unwrap();
+                let own_multiop_try =
+                    [a.clone(), b.clone(), c.clone()].map(Ok::<_, Infallible>).at_least(k).unwrap();
+
+                for roar in &[ref_multiop, own_multiop, ref_multiop_try, own_multiop_try] {
+                    prop_assert_eq!(expected, roar);
+                }
+            }
+            prop_assert!([&a, &b, &c].at_least(4).is_empty());
+        }
+
+        #[test]
+        fn all_multi_len_match_the_materialized_result(
+            a in RoaringTreemap::arbitrary(),
+            b in RoaringTreemap::arbitrary(),
+            c in RoaringTreemap::arbitrary()
+        ) {
+            let union = [&a, &b, &c].union().len();
+            let intersection = [&a, &b, &c].intersection().len();
+            let symmetric_difference = [&a, &b, &c].symmetric_difference().len();
+
+            prop_assert_eq!([&a, &b, &c].union_len(), union);
+            prop_assert_eq!([&a, &b, &c].intersection_len(), intersection);
+            prop_assert_eq!([&a, &b, &c].symmetric_difference_len(), symmetric_difference);
+
+            let bitmaps = [a.clone(), b.clone(), c.clone()];
+            prop_assert_eq!(bitmaps.clone().union_len(), union);
+            prop_assert_eq!(bitmaps.clone().intersection_len(), intersection);
+            prop_assert_eq!(bitmaps.clone().symmetric_difference_len(), symmetric_difference);
+
+            let ref_try = [&a, &b, &c].map(Ok::<_, Infallible>);
+            prop_assert_eq!(ref_try.union_len(), Ok(union));
+            prop_assert_eq!(ref_try.intersection_len(), Ok(intersection));
+            prop_assert_eq!(ref_try.symmetric_difference_len(), Ok(symmetric_difference));
+
+            let own_try = bitmaps.map(Ok::<_, Infallible>);
+            prop_assert_eq!(own_try.clone().union_len(), Ok(union));
+            prop_assert_eq!(own_try.clone().intersection_len(), Ok(intersection));
+            prop_assert_eq!(own_try.symmetric_difference_len(), Ok(symmetric_difference));
+
+            prop_assert_eq!([&a].intersection_len(), a.len());
+            prop_assert_eq!([&a, &b].union_len(), a.union_len(&b));
+            prop_assert_eq!([&a, &b].intersection_len(), a.intersection_len(&b));
+        }
     }
 }
@@ -1,5 +1,5 @@
 use super::RoaringTreemap;
-use crate::RoaringBitmap;
+use crate::{DeserializeError, DeserializeOptions, RoaringBitmap};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::{io, mem::size_of};
 
@@ -68,9 +68,40 @@ impl RoaringTreemap {
     ///
     /// assert_eq!(rb1, rb2);
     /// ```
-    pub fn deserialize_from<R: io::Read>(reader: R) -> io::Result<Self> {
-        RoaringTreemap::deserialize_from_impl(reader, |reader| {
-            RoaringBitmap::deserialize_from(reader)
+    pub fn deserialize_from<R: io::Read>(reader: R) -> Result<Self, DeserializeError> {
+        RoaringTreemap::deserialize_from_impl(
+            reader,
+            &mut DeserializeOptions::new(),
+            |reader, options| RoaringBitmap::deserialize_limited_from(reader, options),
+        )
+    }
+
+    /// Deserialize a bitmap into memory like [`RoaringTreemap::deserialize_from`],
+    /// without exceeding the limits of the given [`DeserializeOptions`]. The limits
+    /// apply to the treemap as a whole, not to each of its bitmaps.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::{DeserializeOptions, RoaringTreemap};
+    ///
+    /// let rb1: RoaringTreemap = (1..4).chain(u64::MAX - 3..u64::MAX).collect();
+    /// let mut bytes = vec![];
+    /// rb1.serialize_into(&mut bytes).unwrap();
+    ///
+    /// let options = DeserializeOptions::new().max_cardinality(6);
+    /// let rb2 = RoaringTreemap::deserialize_with(&bytes[..], &options).unwrap();
+    /// assert_eq!(rb1, rb2);
+    ///
+    /// let options = DeserializeOptions::new().max_containers(1);
+    /// assert!(RoaringTreemap::deserialize_with(&bytes[..], &options).is_err());
+    /// ```
+    pub fn deserialize_with<R: io::Read>(
+        reader: R,
+        options: &DeserializeOptions,
+    ) -> Result<Self, DeserializeError> {
+        RoaringTreemap::deserialize_from_impl(reader, &mut options.clone(), |reader, options| {
+            RoaringBitmap::deserialize_limited_from(reader, options)
         })
     }
 
@@ -91,24 +122,32 @@ impl RoaringTreemap {
     ///
     /// assert_eq!(rb1, rb2);
     /// ```
-    pub fn deserialize_unchecked_from<R: io::Read>(reader: R) -> io::Result<Self> {
-        RoaringTreemap::deserialize_from_impl(reader, |reader| {
-            RoaringBitmap::deserialize_unchecked_from(reader)
-        })
+    pub fn deserialize_unchecked_from<R: io::Read>(reader: R) -> Result<Self, DeserializeError> {
+        RoaringTreemap::deserialize_from_impl(
+            reader,
+            &mut DeserializeOptions::new(),
+            |reader, _| RoaringBitmap::deserialize_unchecked_from(reader),
+        )
     }
 
-    fn deserialize_from_impl<R, F>(mut reader: R, mut deserialize_bitmap: F) -> io::Result<Self>
+    fn deserialize_from_impl<R, F>(
+        mut reader: R,
+        options: &mut DeserializeOptions,
+        mut deserialize_bitmap: F,
+    ) -> Result<Self, DeserializeError>
     where
         R: io::Read,
-        F: FnMut(&mut R) -> io::Result<RoaringBitmap>,
+        F: FnMut(&mut R, &mut DeserializeOptions) -> Result<RoaringBitmap, DeserializeError>,
     {
+        options.consume_bytes(size_of::<u64>())?;
         let size = reader.read_u64::<LittleEndian>()?;
 
         let mut s = Self::new();
 
         for _ in 0..size {
+            options.consume_bytes(size_of::<u32>())?;
             let key = reader.read_u32::<LittleEndian>()?;
-            let bitmap = deserialize_bitmap(&mut reader)?;
+            let bitmap = deserialize_bitmap(&mut reader, options)?;
 
             s.map.insert(key, bitmap);
         }
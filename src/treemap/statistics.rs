@@ -0,0 +1,88 @@
+use crate::RoaringTreemap;
+
+/// Detailed statistics about the containers of a [`RoaringTreemap`],
+/// returned by [`RoaringTreemap::statistics`].
+///
+/// The counters are the sums of the [`Statistics`](crate::bitmap::Statistics)
+/// of the inner bitmaps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// The number of inner bitmaps of the treemap.
+    pub n_bitmaps: u64,
+    /// The number of containers of the treemap.
+    pub n_containers: u64,
+    /// The number of array containers of the treemap.
+    pub n_array_containers: u64,
+    /// The number of bitset containers of the treemap.
+    pub n_bitset_containers: u64,
+    /// The number of run containers of the treemap.
+    pub n_run_containers: u64,
+    /// The number of values stored in array containers.
+    pub n_values_array_containers: u64,
+    /// The number of values stored in bitset containers.
+    pub n_values_bitset_containers: u64,
+    /// The number of values stored in run containers.
+    pub n_values_run_containers: u64,
+    /// The number of bytes used to serialize the values of the array containers.
+    pub n_bytes_array_containers: u64,
+    /// The number of bytes used to serialize the values of the bitset containers.
+    pub n_bytes_bitset_containers: u64,
+    /// The number of bytes used to serialize the values of the run containers.
+    pub n_bytes_run_containers: u64,
+    /// The number of bytes allocated on the heap, as returned by [`RoaringTreemap::memory_usage`].
+    pub n_heap_bytes: u64,
+    /// The number of values of the treemap.
+    pub cardinality: u64,
+    /// The smallest value of the treemap.
+    pub min_value: Option<u64>,
+    /// The greatest value of the treemap.
+    pub max_value: Option<u64>,
+}
+
+impl RoaringTreemap {
+    /// Returns detailed statistics about the containers of this treemap,
+    /// aggregated over all of its inner bitmaps.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use roaring::RoaringTreemap;
+    ///
+    /// let mut rb: RoaringTreemap = (0..10).collect();
+    /// rb.insert_range(u64::MAX - 131_071..=u64::MAX);
+    ///
+    /// let stats = rb.statistics();
+    /// assert_eq!(stats.n_bitmaps, 2);
+    /// assert_eq!(stats.n_containers, 3);
+    /// assert_eq!(stats.n_array_containers, 1);
+    /// assert_eq!(stats.n_run_containers, 2);
+    /// assert_eq!(stats.cardinality, 131_082);
+    /// assert_eq!(stats.max_value, Some(u64::MAX));
+    /// ```
+    pub fn statistics(&self) -> Statistics {
+        let mut stats = Statistics {
+            n_bitmaps: self.map.len() as u64,
+            n_heap_bytes: self.memory_usage() as u64,
+            min_value: self.min(),
+            max_value: self.max(),
+            ..Statistics::default()
+        };
+
+        for bitmap in self.map.values() {
+            let inner = bitmap.statistics();
+            stats.n_containers += inner.n_containers;
+            stats.n_array_containers += inner.n_array_containers;
+            stats.n_bitset_containers += inner.n_bitset_containers;
+            stats.n_run_containers += inner.n_run_containers;
+            stats.n_values_array_containers += inner.n_values_array_containers;
+            stats.n_values_bitset_containers += inner.n_values_bitset_containers;
+            stats.n_values_run_containers += inner.n_values_run_containers;
+            stats.n_bytes_array_containers += inner.n_bytes_array_containers;
+            stats.n_bytes_bitset_containers += inner.n_bytes_bitset_containers;
+            stats.n_bytes_run_containers += inner.n_bytes_run_containers;
+            stats.cardinality += inner.cardinality;
+        }
+
+        stats
+    }
+}
@@ -0,0 +1,57 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringBitmap;
+
+#[test]
+fn add_offset() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    bitmap.insert_range(200_000..210_000);
+    bitmap.insert(u32::MAX);
+
+    // Only the keys are changed
+    let shifted = bitmap.add_offset(1 << 16);
+    assert!(shifted.iter().eq((65_536..67_536).chain(265_536..275_536)));
+    let mut expected = bitmap.clone();
+    expected.remove(u32::MAX);
+    assert_eq!(shifted.add_offset(-(1 << 16)), expected);
+
+    // The containers are split across two keys
+    let shifted = bitmap.add_offset(65_000);
+    assert!(shifted.iter().eq((65_000..67_000).chain(265_000..275_000)));
+
+    assert!(bitmap.add_offset(-200_005).iter().eq((0..9995).chain([u32::MAX - 200_005])));
+    assert!(bitmap.add_offset(i64::MAX).is_empty());
+    assert!(bitmap.add_offset(i64::MIN).is_empty());
+}
+
+proptest! {
+    #[test]
+    fn add_offset_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=5),
+        run_optimize in any::<bool>(),
+        offset in prop_oneof![
+            -400_000..400_000i64,
+            (-6..6i64).prop_map(|n| n << 16),
+            -(1i64 << 33)..(1 << 33),
+        ],
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+
+        let expected: Vec<u32> = bitmap
+            .iter()
+            .filter_map(|value| u32::try_from(i64::from(value) + offset).ok())
+            .collect();
+        let shifted = bitmap.add_offset(offset);
+        prop_assert_eq!(shifted.len(), expected.len() as u64);
+        prop_assert!(shifted.iter().eq(expected));
+    }
+}
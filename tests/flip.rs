@@ -0,0 +1,93 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringBitmap;
+use std::collections::BTreeSet;
+
+#[test]
+fn flip_range() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    bitmap.insert_range(200_000..210_000);
+
+    // Array container
+    bitmap.flip_range(1000..3000);
+    assert_eq!(bitmap.len(), 2000 + 10_000);
+    assert!(bitmap.contains(999) && !bitmap.contains(1000) && bitmap.contains(2999));
+
+    // Missing containers are created and emptied containers are removed
+    bitmap.flip_range(0..1000);
+    bitmap.flip_range(65_536..131_072);
+    assert_eq!(bitmap.min(), Some(2000));
+    assert_eq!(bitmap.len(), 1000 + 65_536 + 10_000);
+
+    // Bitmap container
+    bitmap.flip_range(205_000..=205_009);
+    assert_eq!(bitmap.len(), 1000 + 65_536 + 10_000 - 10);
+
+    bitmap.flip_range(..);
+    assert_eq!(bitmap.len(), (1 << 32) - (1000 + 65_536 + 10_000 - 10));
+    assert!(bitmap.contains(0) && bitmap.contains(u32::MAX));
+}
+
+#[test]
+fn flipped() {
+    let bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+
+    assert_eq!(bitmap.flipped(..).flipped(..), bitmap);
+    assert_eq!(bitmap.flipped(1000..3000), (0..1000).chain(2000..3000).collect());
+    #[allow(clippy::reversed_empty_ranges)]
+    let flipped = bitmap.flipped(3000..1000);
+    assert_eq!(flipped, bitmap);
+}
+
+#[test]
+fn or_not() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter([1, 100_000]).unwrap();
+    let other = RoaringBitmap::from_sorted_iter(2..70_000).unwrap();
+
+    bitmap.or_not(&other, 70_010);
+    assert!(bitmap.iter().eq([0, 1].into_iter().chain(70_000..70_010).chain([100_000])));
+
+    bitmap.or_not(&other, 0);
+    assert_eq!(bitmap.len(), 13);
+
+    bitmap.or_not(&RoaringBitmap::new(), u64::MAX);
+    assert!(bitmap.is_full());
+}
+
+proptest! {
+    #[test]
+    fn flip_range_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=5),
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values.iter().cloned()).unwrap();
+        let mut model = values;
+        for (start, len) in ranges {
+            bitmap.flip_range(start..start + len);
+            for value in start..start + len {
+                if !model.remove(&value) {
+                    model.insert(value);
+                }
+            }
+            prop_assert_eq!(bitmap.len(), model.len() as u64);
+        }
+        prop_assert!(bitmap.iter().eq(model.iter().cloned()));
+    }
+
+    #[test]
+    fn or_not_model(
+        a in btree_set(0..300_000u32, ..=10_000),
+        b in btree_set(0..300_000u32, ..=10_000),
+        universe_end in 0..300_000u64,
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(a.iter().cloned()).unwrap();
+        let other = RoaringBitmap::from_sorted_iter(b.iter().cloned()).unwrap();
+        bitmap.or_not(&other, universe_end);
+
+        let mut expected: BTreeSet<u32> = a;
+        expected.extend((0..universe_end as u32).filter(|v| !b.contains(v)));
+        prop_assert!(bitmap.iter().eq(expected.iter().cloned()));
+    }
+}
@@ -1,7 +1,9 @@
 use proptest::arbitrary::any;
-use proptest::collection::btree_set;
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
 use proptest::proptest;
 use std::iter::FromIterator;
+use std::ops::RangeInclusive;
 
 use roaring::RoaringBitmap;
 
@@ -99,7 +101,7 @@ fn from_iter() {
     // with u32 as well as &u32 elements.
     let vals = vec![1, 5, 10000];
     let a = RoaringBitmap::from_iter(vals.iter());
-    let b = RoaringBitmap::from_iter(vals.into_iter());
+    let b = RoaringBitmap::from_iter(vals);
     assert_eq!(a, b);
 }
 
@@ -158,3 +160,236 @@ proptest! {
         assert!(outside_in(values).eq(outside_in(bitmap)));
     }
 }
+
+#[test]
+fn advance_to() {
+    let bitmap =
+        (0..10).chain(100_000..200_000).chain(1_000_000..1_000_010).collect::<RoaringBitmap>();
+    let mut iter = bitmap.iter();
+
+    iter.advance_to(5);
+    assert_eq!(iter.next(), Some(5));
+    iter.advance_to(50);
+    assert_eq!(iter.next(), Some(100_000));
+    iter.advance_to(150_000);
+    assert_eq!(iter.len(), 50_010);
+    iter.advance_back_to(1_000_004);
+    assert_eq!(iter.next_back(), Some(1_000_004));
+    iter.advance_back_to(500_000);
+    assert_eq!(iter.next_back(), Some(199_999));
+    assert_eq!(iter.len(), 49_999);
+    iter.advance_to(u32::MAX);
+    assert_eq!(iter.len(), 0);
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn next_many() {
+    let mut bitmap =
+        (0..10).chain(100_000..200_000).chain(1_000_000..1_000_010).collect::<RoaringBitmap>();
+    let expected: Vec<u32> = bitmap.iter().collect();
+    let mut buf = vec![0; 30_000];
+
+    for run_optimize in [false, true] {
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+        let mut iter = bitmap.iter();
+        let mut values = Vec::new();
+        while let Some(value) = iter.next() {
+            values.push(value);
+            let n = iter.next_many(&mut buf);
+            values.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(values, expected);
+        assert_eq!(iter.next_many(&mut buf), 0);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum IterOp {
+    Next,
+    NextBack,
+    AdvanceTo(u32),
+    AdvanceBackTo(u32),
+    NextMany(usize),
+}
+
+fn iter_op() -> impl Strategy<Value = IterOp> {
+    prop_oneof![
+        Just(IterOp::Next),
+        Just(IterOp::NextBack),
+        (0..300_000u32).prop_map(IterOp::AdvanceTo),
+        (0..300_000u32).prop_map(IterOp::AdvanceBackTo),
+        (0..100_000usize).prop_map(IterOp::NextMany),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn advance_iter(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..10_000u32), ..=10),
+        run_optimize in any::<bool>(),
+        ops in vec(iter_op(), ..=50),
+    ) {
+        let mut values: Vec<u32> = values.into_iter().collect();
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values.iter().cloned()).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+        values = bitmap.iter().collect();
+
+        let mut iter = bitmap.iter();
+        let mut into_iter = bitmap.clone().into_iter();
+        let mut remaining = &values[..];
+        for op in ops {
+            match op {
+                IterOp::Next => {
+                    let expected = remaining.first().cloned();
+                    remaining = remaining.get(1..).unwrap_or_default();
+                    prop_assert_eq!(iter.next(), expected);
+                    prop_assert_eq!(into_iter.next(), expected);
+                }
+                IterOp::NextBack => {
+                    let expected = remaining.last().cloned();
+                    remaining = &remaining[..remaining.len().saturating_sub(1)];
+                    prop_assert_eq!(iter.next_back(), expected);
+                    prop_assert_eq!(into_iter.next_back(), expected);
+                }
+                IterOp::AdvanceTo(n) => {
+                    remaining = &remaining[remaining.partition_point(|&v| v < n)..];
+                    iter.advance_to(n);
+                    into_iter.advance_to(n);
+                }
+                IterOp::AdvanceBackTo(n) => {
+                    remaining = &remaining[..remaining.partition_point(|&v| v <= n)];
+                    iter.advance_back_to(n);
+                    into_iter.advance_back_to(n);
+                }
+                IterOp::NextMany(n) => {
+                    let expected = &remaining[..n.min(remaining.len())];
+                    remaining = &remaining[expected.len()..];
+                    let mut buf = vec![0; n];
+                    prop_assert_eq!(iter.next_many(&mut buf), expected.len());
+                    prop_assert_eq!(&buf[..expected.len()], expected);
+                    prop_assert_eq!(into_iter.next_many(&mut buf), expected.len());
+                    prop_assert_eq!(&buf[..expected.len()], expected);
+                }
+            }
+            prop_assert_eq!(iter.len(), remaining.len());
+            prop_assert_eq!(into_iter.len(), remaining.len());
+        }
+        prop_assert!(iter.eq(remaining.iter().cloned()));
+        prop_assert!(into_iter.eq(remaining.iter().cloned()));
+    }
+}
+
+#[test]
+fn range_bounds() {
+    let bitmap =
+        (0..10).chain(100_000..200_000).chain(1_000_000..1_000_010).collect::<RoaringBitmap>();
+
+    assert!(bitmap.range(5..100_005).eq((5..10).chain(100_000..100_005)));
+    assert!(bitmap.range(150_000..).rev().eq((150_000..200_000).chain(1_000_000..1_000_010).rev()));
+    assert_eq!(bitmap.range(..=1_000_000).len(), 100_011);
+    assert_eq!(bitmap.range(10..100_000).next(), None);
+    #[allow(clippy::reversed_empty_ranges)]
+    let empty = bitmap.range(10..5);
+    assert_eq!(empty.len(), 0);
+}
+
+proptest! {
+    #[test]
+    fn range_iter(
+        values in btree_set(any::<u32>(), ..=10_000),
+        start in any::<u32>(),
+        end in any::<u32>(),
+    ) {
+        let bitmap = RoaringBitmap::from_sorted_iter(values.iter().cloned()).unwrap();
+        let expected = || values.range(start..=end.max(start)).cloned();
+
+        let range = bitmap.range(start..=end.max(start));
+        prop_assert_eq!(range.len(), expected().count());
+        prop_assert!(range.eq(expected()));
+        prop_assert!(bitmap.range(start..=end.max(start)).rev().eq(expected().rev()));
+    }
+}
+
+/// Groups sorted values into maximal ranges of consecutive values.
+pub fn to_ranges<T>(values: impl IntoIterator<Item = T>) -> Vec<RangeInclusive<T>>
+where
+    T: Copy + PartialEq + std::ops::Add<Output = T> + From<u8>,
+{
+    let mut ranges: Vec<RangeInclusive<T>> = Vec::new();
+    for value in values {
+        match ranges.last_mut() {
+            Some(last) if *last.end() + T::from(1) == value => *last = *last.start()..=value,
+            _ => ranges.push(value..=value),
+        }
+    }
+    ranges
+}
+
+#[test]
+fn iter_ranges() {
+    let mut bitmap = (0..10)
+        .chain(65_530..65_536)
+        .chain(65_536..140_000)
+        .chain([140_002, 140_004, 140_005])
+        .chain(u32::MAX - 1..=u32::MAX)
+        .collect::<RoaringBitmap>();
+    let expected =
+        [0..=9, 65_530..=139_999, 140_002..=140_002, 140_004..=140_005, u32::MAX - 1..=u32::MAX];
+
+    assert!(bitmap.iter_ranges().eq(expected.iter().cloned()));
+    assert!(bitmap.iter_ranges().rev().eq(expected.iter().cloned().rev()));
+    bitmap.run_optimize();
+    assert!(bitmap.iter_ranges().eq(expected.iter().cloned()));
+
+    let mut ranges = bitmap.iter_ranges();
+    assert_eq!(ranges.next_back(), Some(u32::MAX - 1..=u32::MAX));
+    assert_eq!(ranges.next(), Some(0..=9));
+    assert_eq!(ranges.next_back(), Some(140_004..=140_005));
+    assert_eq!(ranges.next_back(), Some(140_002..=140_002));
+    assert_eq!(ranges.next_back(), Some(65_530..=139_999));
+    assert_eq!(ranges.next(), None);
+}
+
+proptest! {
+    #[test]
+    fn iter_ranges_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=10),
+        run_optimize in any::<bool>(),
+        backward in vec(any::<bool>(), ..=100),
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values.iter().cloned()).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+        let expected = to_ranges(bitmap.iter());
+
+        prop_assert!(bitmap.iter_ranges().eq(expected.iter().cloned()));
+        prop_assert!(bitmap.iter_ranges().rev().eq(expected.iter().cloned().rev()));
+
+        let mut iter = bitmap.iter_ranges();
+        let mut remaining = &expected[..];
+        for backward in backward {
+            if backward {
+                prop_assert_eq!(iter.next_back(), remaining.last().cloned());
+                remaining = &remaining[..remaining.len().saturating_sub(1)];
+            } else {
+                prop_assert_eq!(iter.next(), remaining.first().cloned());
+                remaining = remaining.get(1..).unwrap_or_default();
+            }
+        }
+        prop_assert!(iter.eq(remaining.iter().cloned()));
+    }
+}
@@ -0,0 +1,57 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringBitmap;
+
+#[test]
+fn memory_usage() {
+    assert_eq!(RoaringBitmap::new().memory_usage(), 0);
+
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..4000).unwrap();
+    bitmap.insert_range(131_072..262_144);
+    bitmap.remove_run_compression();
+    let usage = bitmap.memory_usage();
+    assert!(usage >= 4000 * 2 + 2 * 8192);
+    assert_eq!(usage, bitmap.statistics().n_heap_bytes as usize);
+
+    // Removing values keeps the capacity of the arrays and the container vector
+    bitmap.remove_range(10..4000);
+    assert_eq!(bitmap.memory_usage(), usage);
+    bitmap.remove_range(131_072..262_144);
+    assert_eq!(bitmap.memory_usage(), usage - 2 * 8192);
+
+    bitmap.shrink_to_fit();
+    assert!(bitmap.memory_usage() < 100);
+    assert!(bitmap.iter().eq(0..10));
+
+    bitmap.clear();
+    bitmap.shrink_to_fit();
+    assert_eq!(bitmap.memory_usage(), 0);
+}
+
+proptest! {
+    #[test]
+    fn shrink_to_fit_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=5),
+        run_optimize in any::<bool>(),
+        removed in 0..300_000u32,
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+        bitmap.remove_range(removed..);
+        let original = bitmap.clone();
+
+        let usage = bitmap.memory_usage();
+        bitmap.shrink_to_fit();
+        prop_assert!(bitmap.memory_usage() <= usage);
+        prop_assert_eq!(bitmap.memory_usage(), bitmap.clone().memory_usage());
+        prop_assert_eq!(bitmap, original);
+    }
+}
@@ -0,0 +1,77 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringBitmap;
+use std::collections::BTreeSet;
+
+#[test]
+fn next_value() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    bitmap.insert_range(200_000..300_000);
+    bitmap.insert(u32::MAX);
+
+    assert_eq!(bitmap.next_value(0), Some(0));
+    assert_eq!(bitmap.next_value(2000), Some(200_000));
+    assert_eq!(bitmap.next_value(250_000), Some(250_000));
+    assert_eq!(bitmap.next_value(300_000), Some(u32::MAX));
+
+    assert_eq!(bitmap.previous_value(u32::MAX - 1), Some(299_999));
+    assert_eq!(bitmap.previous_value(199_999), Some(1999));
+    assert_eq!(bitmap.previous_value(0), Some(0));
+
+    bitmap.remove(u32::MAX);
+    assert_eq!(bitmap.next_value(300_000), None);
+    bitmap.remove(0);
+    assert_eq!(bitmap.previous_value(0), None);
+}
+
+#[test]
+fn next_absent() {
+    // The containers of the keys 3 and 4 are full
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    bitmap.insert_range(200_000..400_000);
+
+    assert_eq!(bitmap.next_absent(0), Some(2000));
+    assert_eq!(bitmap.next_absent(2000), Some(2000));
+    assert_eq!(bitmap.next_absent(200_000), Some(400_000));
+    assert_eq!(bitmap.previous_absent(399_999), Some(199_999));
+    assert_eq!(bitmap.previous_absent(1999), None);
+
+    bitmap.insert_range(400_000..);
+    assert_eq!(bitmap.next_absent(200_000), None);
+    assert_eq!(bitmap.previous_absent(u32::MAX), Some(199_999));
+
+    bitmap.run_optimize();
+    assert_eq!(bitmap.next_absent(200_000), None);
+    assert_eq!(bitmap.previous_absent(u32::MAX), Some(199_999));
+}
+
+proptest! {
+    #[test]
+    fn next_value_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=5),
+        run_optimize in any::<bool>(),
+        queries in vec(0..400_000u32, ..=100),
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+        let model: BTreeSet<u32> = bitmap.iter().collect();
+
+        for value in queries {
+            prop_assert_eq!(bitmap.next_value(value), model.range(value..).next().cloned());
+            prop_assert_eq!(bitmap.previous_value(value), model.range(..=value).next_back().cloned());
+            prop_assert_eq!(bitmap.next_absent(value), (value..).find(|v| !model.contains(v)));
+            prop_assert_eq!(
+                bitmap.previous_absent(value),
+                (0..=value).rev().find(|v| !model.contains(v))
+            );
+        }
+    }
+}
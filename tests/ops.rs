@@ -76,3 +76,30 @@ fn xor() {
 
     assert_eq!(rb4, rb1);
 }
+
+#[test]
+fn multi_or() {
+    use roaring::MultiOps;
+
+    // Two arrays whose estimated union overflows an array, but not their actual union
+    let rb1 = (0..3000).collect::<RoaringBitmap>();
+    let rb2 = (1000..4000).collect::<RoaringBitmap>();
+    // A bitmap, a run and a sparse array
+    let rb3 = (70_000..80_000).collect::<RoaringBitmap>();
+    let mut rb4 = RoaringBitmap::new();
+    rb4.insert_range(75_000..140_000);
+    let rb5 = (0..200_000).step_by(7).collect::<RoaringBitmap>();
+
+    let bitmaps = [rb1, rb2, rb3, rb4, rb5];
+    let expected = bitmaps.iter().fold(RoaringBitmap::new(), |acc, rb| acc | rb);
+
+    let ref_multiop = bitmaps.iter().union();
+    let own_multiop = bitmaps.clone().union();
+
+    assert_eq!(expected, ref_multiop);
+    assert_eq!(expected, own_multiop);
+    assert_eq!(expected.len(), ref_multiop.len());
+    assert_eq!(expected.len(), own_multiop.len());
+    assert_eq!(expected.statistics(), ref_multiop.statistics());
+    assert_eq!(expected.statistics(), own_multiop.statistics());
+}
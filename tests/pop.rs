@@ -0,0 +1,74 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringBitmap;
+
+#[test]
+fn pop() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter([1, 70_000, u32::MAX]).unwrap();
+
+    assert_eq!(bitmap.pop_min(), Some(1));
+    assert_eq!(bitmap.pop_max(), Some(u32::MAX));
+    assert_eq!(bitmap.pop_max(), Some(70_000));
+    assert_eq!(bitmap.pop_min(), None);
+    assert_eq!(bitmap.pop_max(), None);
+    assert!(bitmap.is_empty());
+}
+
+#[test]
+fn remove_smallest_biggest() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    bitmap.insert_range(200_000..300_000);
+
+    assert_eq!(bitmap.remove_smallest(0), 0);
+    assert_eq!(bitmap.remove_smallest(3000), 3000);
+    assert!(bitmap.iter().eq(201_000..300_000));
+
+    assert_eq!(bitmap.remove_biggest(0), 0);
+    assert_eq!(bitmap.remove_biggest(50_000), 50_000);
+    assert!(bitmap.iter().eq(201_000..250_000));
+
+    assert_eq!(bitmap.remove_biggest(u64::MAX), 49_000);
+    assert!(bitmap.is_empty());
+    assert_eq!(bitmap.remove_smallest(1), 0);
+
+    let mut bitmap = RoaringBitmap::full();
+    assert_eq!(bitmap.remove_smallest(1 << 31), 1 << 31);
+    assert_eq!(bitmap.min(), Some(1 << 31));
+    assert_eq!(bitmap.remove_biggest(1), 1);
+    assert_eq!(bitmap.max(), Some(u32::MAX - 1));
+}
+
+proptest! {
+    #[test]
+    fn remove_smallest_biggest_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=5),
+        run_optimize in any::<bool>(),
+        smallest in 0..200_000u64,
+        biggest in 0..200_000u64,
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+        let mut model: Vec<u32> = bitmap.iter().collect();
+
+        let removed = bitmap.remove_smallest(smallest);
+        prop_assert_eq!(removed, smallest.min(model.len() as u64));
+        model.drain(..removed as usize);
+        prop_assert!(bitmap.iter().eq(model.iter().cloned()));
+
+        let removed = bitmap.remove_biggest(biggest);
+        prop_assert_eq!(removed, biggest.min(model.len() as u64));
+        model.truncate(model.len() - removed as usize);
+        prop_assert!(bitmap.iter().eq(model.iter().cloned()));
+
+        prop_assert_eq!(bitmap.pop_min(), model.first().cloned());
+        prop_assert_eq!(bitmap.pop_max(), model.get(1..).and_then(|m| m.last()).cloned());
+    }
+}
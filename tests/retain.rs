@@ -0,0 +1,92 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringBitmap;
+
+#[test]
+fn retain() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    bitmap.insert_range(200_000..300_000);
+
+    // The bitmap containers become arrays and the emptied containers are removed
+    bitmap.retain(|value| value < 1000 || value % 100 == 0);
+    assert_eq!(bitmap.len(), 1000 + 10 + 1000);
+    bitmap.retain(|value| value >= 200_000);
+    assert!(bitmap.iter().eq((200_000..300_000).step_by(100)));
+}
+
+#[test]
+fn extract_if() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    bitmap.insert_range(200_000..300_000);
+
+    let extracted = bitmap.extract_if(|value| value >= 1000);
+    assert!(bitmap.iter().eq(0..1000));
+    assert!(extracted.iter().eq((1000..2000).chain(200_000..300_000)));
+    assert!(bitmap.extract_if(|_| false).is_empty());
+}
+
+#[test]
+fn drain_range() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    bitmap.insert_range(200_000..300_000);
+
+    let drained = bitmap.drain_range(1000..250_000);
+    assert!(bitmap.iter().eq((0..1000).chain(250_000..300_000)));
+    assert!(drained.iter().eq((1000..2000).chain(200_000..250_000)));
+
+    let drained = bitmap.drain_range(..);
+    assert!(bitmap.is_empty());
+    assert!(drained.iter().eq((0..1000).chain(250_000..300_000)));
+}
+
+proptest! {
+    #[test]
+    fn retain_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=5),
+        run_optimize in any::<bool>(),
+        modulo in 1..10u32,
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+        let original = bitmap.clone();
+
+        let mut visited = Vec::new();
+        bitmap.retain(|value| {
+            visited.push(value);
+            value % modulo == 0
+        });
+        prop_assert!(visited.iter().cloned().eq(original.iter()));
+        prop_assert!(bitmap.iter().eq(original.iter().filter(|value| value % modulo == 0)));
+
+        let mut remaining = original.clone();
+        let extracted = remaining.extract_if(|value| value % modulo != 0);
+        prop_assert_eq!(&remaining, &bitmap);
+        prop_assert_eq!(extracted, &original - &bitmap);
+    }
+
+    #[test]
+    fn drain_range_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=5),
+        start in 0..300_000u32,
+        len in 0..200_000u32,
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        let original = bitmap.clone();
+
+        let drained = bitmap.drain_range(start..start + len);
+        prop_assert!(drained.iter().eq(original.iter().filter(|v| (start..start + len).contains(v))));
+        prop_assert!(bitmap.iter().eq(original.iter().filter(|v| !(start..start + len).contains(v))));
+    }
+}
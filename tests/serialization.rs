@@ -1,9 +1,10 @@
 extern crate roaring;
 
-use roaring::RoaringBitmap;
+use roaring::{RoaringBitmap, RoaringBitmapView};
 
 // Test data from https://github.com/RoaringBitmap/RoaringFormatSpec/tree/master/testdata
 static BITMAP_WITHOUT_RUNS: &[u8] = include_bytes!("bitmapwithoutruns.bin");
+static BITMAP_WITH_RUNS: &[u8] = include_bytes!("bitmapwithruns.bin");
 
 fn test_data_bitmap() -> RoaringBitmap {
     (0..100)
@@ -25,6 +26,26 @@ fn test_deserialize_from_provided_data() {
     assert_eq!(RoaringBitmap::deserialize_from(BITMAP_WITHOUT_RUNS).unwrap(), test_data_bitmap());
 }
 
+#[test]
+fn test_deserialize_with_runs_from_provided_data() {
+    assert_eq!(RoaringBitmap::deserialize_from(BITMAP_WITH_RUNS).unwrap(), test_data_bitmap());
+    assert_eq!(
+        RoaringBitmap::deserialize_unchecked_from(BITMAP_WITH_RUNS).unwrap(),
+        test_data_bitmap()
+    );
+}
+
+#[test]
+fn test_view_provided_data() {
+    let expected = test_data_bitmap();
+    for bytes in [BITMAP_WITHOUT_RUNS, BITMAP_WITH_RUNS] {
+        let view = RoaringBitmapView::new(bytes).unwrap();
+        assert_eq!(view.len(), expected.len());
+        assert!(view.iter().eq(expected.iter()));
+        assert_eq!(view.to_bitmap(), expected);
+    }
+}
+
 #[test]
 fn test_serialize_into_provided_data() {
     let bitmap = test_data_bitmap();
@@ -33,6 +54,42 @@ fn test_serialize_into_provided_data() {
     assert!(BITMAP_WITHOUT_RUNS == &buffer[..]);
 }
 
+#[test]
+fn test_serialize_into_provided_data_with_runs() {
+    let mut bitmap = test_data_bitmap();
+    assert!(bitmap.run_optimize());
+    let mut buffer = vec![];
+    bitmap.serialize_into(&mut buffer).unwrap();
+    assert_eq!(buffer.len(), bitmap.serialized_size());
+    assert!(BITMAP_WITH_RUNS == &buffer[..]);
+}
+
+#[test]
+fn test_remove_run_compression_provided_data() {
+    let mut bitmap = RoaringBitmap::deserialize_from(BITMAP_WITH_RUNS).unwrap();
+    assert!(bitmap.remove_run_compression());
+    let mut buffer = vec![];
+    bitmap.serialize_into(&mut buffer).unwrap();
+    assert!(BITMAP_WITHOUT_RUNS == &buffer[..]);
+}
+
+#[test]
+fn test_runs_without_offsets() {
+    let mut original = (0..10).chain(100_000..200_000).collect::<RoaringBitmap>();
+    original.run_optimize();
+    let new = serialize_and_deserialize(&original);
+    assert_eq!(original, new);
+}
+
+#[test]
+fn test_runs_with_offsets() {
+    let mut original =
+        (0..10).map(|i| i << 16).chain(1_000_000..2_000_000).collect::<RoaringBitmap>();
+    original.run_optimize();
+    let new = serialize_and_deserialize(&original);
+    assert_eq!(original, new);
+}
+
 #[test]
 fn test_empty() {
     let original = RoaringBitmap::new();
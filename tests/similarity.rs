@@ -0,0 +1,85 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringBitmap;
+
+#[test]
+fn similarity() {
+    let mut a = RoaringBitmap::from_sorted_iter(0..3000).unwrap();
+    a.insert_range(100_000..200_000);
+    let b = RoaringBitmap::from_sorted_iter((2000..4000).chain(150_000..150_500)).unwrap();
+
+    let intersection = (1000 + 500) as f64;
+    let union = (103_000 + 1000) as f64;
+    assert_eq!(a.jaccard_index(&b), intersection / union);
+    assert_eq!(a.overlap_coefficient(&b), intersection / 2500.0);
+    assert_eq!(a.dice_coefficient(&b), 2.0 * intersection / 105_500.0);
+    assert_eq!(a.hamming_distance(&b), 103_000 + 2500 - 2 * 1500);
+
+    assert_eq!(a.jaccard_index(&a), 1.0);
+    assert_eq!(a.overlap_coefficient(&b), b.overlap_coefficient(&a));
+    assert_eq!(a.hamming_distance(&a), 0);
+}
+
+#[test]
+fn similarity_empty() {
+    let empty = RoaringBitmap::new();
+    let a = RoaringBitmap::from_sorted_iter(0..10).unwrap();
+
+    assert!(empty.jaccard_index(&empty).is_nan());
+    assert!(empty.dice_coefficient(&empty).is_nan());
+    assert!(empty.overlap_coefficient(&a).is_nan());
+    assert_eq!(empty.jaccard_index(&a), 0.0);
+    assert_eq!(empty.dice_coefficient(&a), 0.0);
+    assert_eq!(empty.hamming_distance(&a), 10);
+}
+
+#[test]
+fn intersection_len_many() {
+    let mut a = RoaringBitmap::from_sorted_iter(0..3000).unwrap();
+    a.insert_range(100_000..200_000);
+    let b = RoaringBitmap::from_sorted_iter((2000..4000).chain(150_000..150_500)).unwrap();
+    let c = RoaringBitmap::from_sorted_iter(500_000..600_000).unwrap();
+
+    assert_eq!(a.intersection_len_many(&[]), Vec::<u64>::new());
+    assert_eq!(a.intersection_len_many(&[&b, &c, &a, &b]), vec![1500, 0, a.len(), 1500]);
+    assert_eq!(RoaringBitmap::new().intersection_len_many(&[&a, &b]), vec![0, 0]);
+}
+
+proptest! {
+    #[test]
+    fn similarity_model(
+        a in btree_set(0..300_000u32, ..=50_000),
+        b in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=3),
+        run_optimize in any::<bool>(),
+    ) {
+        let mut a = RoaringBitmap::from_sorted_iter(a).unwrap();
+        let mut b = RoaringBitmap::from_sorted_iter(b).unwrap();
+        for (i, (start, len)) in ranges.into_iter().enumerate() {
+            if i % 2 == 0 { &mut a } else { &mut b }.insert_range(start..start + len);
+        }
+        if run_optimize {
+            a.run_optimize();
+        }
+
+        let intersection = (&a & &b).len();
+        let union = (&a | &b).len();
+        prop_assume!(!a.is_empty() && !b.is_empty());
+        prop_assert_eq!(a.jaccard_index(&b), intersection as f64 / union as f64);
+        prop_assert_eq!(
+            a.overlap_coefficient(&b),
+            intersection as f64 / a.len().min(b.len()) as f64
+        );
+        prop_assert_eq!(
+            a.dice_coefficient(&b),
+            2.0 * intersection as f64 / (a.len() + b.len()) as f64
+        );
+        prop_assert_eq!(a.hamming_distance(&b), (&a ^ &b).len());
+        prop_assert_eq!(
+            a.intersection_len_many(&[&b, &a]),
+            vec![intersection, a.len()]
+        );
+    }
+}
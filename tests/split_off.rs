@@ -0,0 +1,73 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringBitmap;
+
+#[test]
+fn split_off() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    bitmap.insert_range(200_000..300_000);
+
+    let high = bitmap.split_off(250_000);
+    assert!(bitmap.iter().eq((0..2000).chain(200_000..250_000)));
+    assert!(high.iter().eq(250_000..300_000));
+
+    // Splitting at a container boundary doesn't leave empty containers
+    let high = bitmap.split_off(1 << 16);
+    assert!(bitmap.iter().eq(0..2000));
+    assert!(high.iter().eq(200_000..250_000));
+
+    let high = bitmap.split_off(0);
+    assert!(bitmap.is_empty());
+    assert!(high.iter().eq(0..2000));
+
+    let mut bitmap = RoaringBitmap::from_sorted_iter([0, u32::MAX]).unwrap();
+    assert!(bitmap.split_off(u32::MAX).iter().eq([u32::MAX]));
+    assert!(bitmap.split_off(1).is_empty());
+    assert!(bitmap.iter().eq([0]));
+}
+
+#[test]
+fn append_bitmap() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..2000).unwrap();
+    let other = RoaringBitmap::from_sorted_iter((2000..4000).chain(200_000..300_000)).unwrap();
+
+    assert_eq!(bitmap.append_bitmap(other), Ok(102_000));
+    assert!(bitmap.iter().eq((0..4000).chain(200_000..300_000)));
+
+    assert_eq!(bitmap.append_bitmap(RoaringBitmap::new()), Ok(0));
+    let other = RoaringBitmap::from_sorted_iter([299_999, 300_000]).unwrap();
+    assert_eq!(bitmap.append_bitmap(other.clone()), Err(other));
+    assert_eq!(bitmap.len(), 104_000);
+
+    let mut empty = RoaringBitmap::new();
+    assert_eq!(empty.append_bitmap(bitmap.clone()), Ok(104_000));
+    assert_eq!(empty, bitmap);
+}
+
+proptest! {
+    #[test]
+    fn split_off_append_bitmap_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=5),
+        run_optimize in any::<bool>(),
+        at in 0..400_000u32,
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+        let original = bitmap.clone();
+
+        let high = bitmap.split_off(at);
+        prop_assert!(bitmap.iter().eq(original.iter().filter(|&v| v < at)));
+        prop_assert!(high.iter().eq(original.iter().filter(|&v| v >= at)));
+
+        prop_assert_eq!(bitmap.append_bitmap(high.clone()), Ok(high.len()));
+        prop_assert_eq!(bitmap, original);
+    }
+}
@@ -0,0 +1,97 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::bitmap::Statistics;
+use roaring::RoaringBitmap;
+
+#[test]
+fn statistics_empty() {
+    let stats = RoaringBitmap::new().statistics();
+    assert_eq!(stats, Statistics::default());
+}
+
+#[test]
+fn statistics() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter((0..3000).step_by(3)).unwrap();
+    bitmap.insert_range(131_072..262_144);
+    bitmap.insert_range(300_000..300_010);
+    bitmap.run_optimize();
+
+    let stats = bitmap.statistics();
+    assert_eq!(stats.n_containers, 4);
+    assert_eq!(stats.n_array_containers, 1);
+    assert_eq!(stats.n_bitset_containers, 0);
+    assert_eq!(stats.n_run_containers, 3);
+    assert_eq!(stats.n_values_array_containers, 1000);
+    assert_eq!(stats.n_values_run_containers, 131_082);
+    assert_eq!(stats.n_bytes_array_containers, 2000);
+    assert_eq!(stats.n_bytes_run_containers, 3 * (2 + 4));
+    assert_eq!(stats.cardinality, bitmap.len());
+    assert_eq!(stats.min_value, Some(0));
+    assert_eq!(stats.max_value, Some(300_009));
+
+    bitmap.remove_run_compression();
+    let stats = bitmap.statistics();
+    assert_eq!(stats.n_bitset_containers, 2);
+    assert_eq!(stats.n_values_bitset_containers, 131_072);
+    assert_eq!(stats.n_bytes_bitset_containers, 2 * 8192);
+    assert!(stats.n_heap_bytes >= 2 * 8192 + 2 * 1010);
+}
+
+#[test]
+fn statistics_heap_bytes_count_spare_capacity() {
+    let mut bitmap = RoaringBitmap::from_sorted_iter(0..4000).unwrap();
+    let before = bitmap.statistics();
+    bitmap.remove_range(10..4000);
+    let after = bitmap.statistics();
+
+    // The values are removed but the capacity of the array is kept
+    assert_eq!(after.n_bytes_array_containers, 20);
+    assert!(after.n_heap_bytes >= 8000);
+    assert!(after.n_heap_bytes <= before.n_heap_bytes);
+}
+
+proptest! {
+    #[test]
+    fn statistics_model(
+        values in btree_set(0..300_000u32, ..=50_000),
+        ranges in vec((0..300_000u32, 0..100_000u32), ..=5),
+        run_optimize in any::<bool>(),
+    ) {
+        let mut bitmap = RoaringBitmap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            bitmap.insert_range(start..start + len);
+        }
+        if run_optimize {
+            bitmap.run_optimize();
+        }
+
+        let stats = bitmap.statistics();
+        prop_assert_eq!(
+            stats.n_containers,
+            stats.n_array_containers + stats.n_bitset_containers + stats.n_run_containers
+        );
+        prop_assert_eq!(
+            stats.cardinality,
+            stats.n_values_array_containers
+                + stats.n_values_bitset_containers
+                + stats.n_values_run_containers
+        );
+        prop_assert_eq!(stats.cardinality, bitmap.len());
+        prop_assert_eq!(stats.min_value, bitmap.min());
+        prop_assert_eq!(stats.max_value, bitmap.max());
+
+        // The serialized size is the payloads of the containers plus the headers
+        let payloads = stats.n_bytes_array_containers
+            + stats.n_bytes_bitset_containers
+            + stats.n_bytes_run_containers;
+        let n = stats.n_containers;
+        let headers = if stats.n_run_containers > 0 {
+            4 + (n + 7) / 8 + 4 * n + if n >= 4 { 4 * n } else { 0 }
+        } else {
+            8 + 8 * n
+        };
+        prop_assert_eq!(payloads + headers, bitmap.serialized_size() as u64);
+    }
+}
@@ -0,0 +1,44 @@
+extern crate roaring;
+
+use proptest::collection::btree_set;
+use proptest::prelude::*;
+use roaring::RoaringTreemap;
+
+const BITMAP_MAX: u64 = u32::MAX as u64;
+
+#[test]
+fn add_offset() {
+    let treemap = RoaringTreemap::from_sorted_iter(BITMAP_MAX - 1000..BITMAP_MAX + 5000).unwrap();
+
+    // Only the keys are changed
+    let shifted = treemap.add_offset(1 << 32);
+    assert!(shifted.iter().eq(BITMAP_MAX * 2 - 999..BITMAP_MAX * 2 + 5001));
+    assert_eq!(shifted.add_offset(-(1 << 32)), treemap);
+
+    // The bitmaps are split across two keys
+    let shifted = treemap.add_offset(2000);
+    assert!(shifted.iter().eq(BITMAP_MAX + 1000..BITMAP_MAX + 7000));
+
+    assert!(treemap.add_offset(-(BITMAP_MAX as i64)).iter().eq(0..5000));
+    assert!(treemap.add_offset(i64::MIN).is_empty());
+}
+
+proptest! {
+    #[test]
+    fn add_offset_model(
+        values in btree_set(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, ..=10_000),
+        offset in prop_oneof![
+            -(1i64 << 33)..(1 << 33),
+            (-3..3i64).prop_map(|n| n << 32),
+            Just(i64::MAX),
+        ],
+    ) {
+        let treemap = RoaringTreemap::from_sorted_iter(values.iter().cloned()).unwrap();
+
+        let expected = values.iter().filter_map(|&value| {
+            let value = i128::from(value) + i128::from(offset);
+            u64::try_from(value).ok()
+        });
+        prop_assert!(treemap.add_offset(offset).iter().eq(expected));
+    }
+}
@@ -0,0 +1,78 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringTreemap;
+use std::collections::BTreeSet;
+
+const BITMAP_MAX: u64 = u32::MAX as u64;
+
+#[test]
+fn flip_range() {
+    let mut treemap =
+        RoaringTreemap::from_sorted_iter(BITMAP_MAX - 1000..BITMAP_MAX + 5000).unwrap();
+
+    treemap.flip_range(BITMAP_MAX - 2000..BITMAP_MAX + 2000);
+    assert!(treemap
+        .iter()
+        .eq((BITMAP_MAX - 2000..BITMAP_MAX - 1000).chain(BITMAP_MAX + 2000..BITMAP_MAX + 5000)));
+
+    // Emptied bitmaps are removed
+    treemap.flip_range(BITMAP_MAX - 2000..BITMAP_MAX - 1000);
+    assert_eq!(treemap.bitmaps().count(), 1);
+
+    treemap.flip_range(u64::MAX - 10..);
+    assert_eq!(treemap.len(), 3000 + 11);
+    assert_eq!(
+        treemap.flipped(BITMAP_MAX..BITMAP_MAX << 2).flipped(BITMAP_MAX..BITMAP_MAX << 2),
+        treemap
+    );
+}
+
+#[test]
+fn or_not() {
+    let mut treemap = RoaringTreemap::from_sorted_iter([1, u64::MAX]).unwrap();
+    let mut other = RoaringTreemap::new();
+    other.insert_range(2..BITMAP_MAX + 10);
+
+    treemap.or_not(&other, BITMAP_MAX + 20);
+    assert!(treemap
+        .iter()
+        .eq([0, 1].into_iter().chain(BITMAP_MAX + 10..BITMAP_MAX + 20).chain([u64::MAX])));
+}
+
+proptest! {
+    #[test]
+    fn flip_range_model(
+        values in btree_set(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, ..=10_000),
+        ranges in vec((BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, 0..20_000u64), ..=5),
+    ) {
+        let mut treemap = RoaringTreemap::from_sorted_iter(values.iter().cloned()).unwrap();
+        let mut model = values;
+        for (start, len) in ranges {
+            treemap.flip_range(start..start + len);
+            for value in start..start + len {
+                if !model.remove(&value) {
+                    model.insert(value);
+                }
+            }
+        }
+        prop_assert!(treemap.iter().eq(model.iter().cloned()));
+    }
+
+    #[test]
+    fn or_not_model(
+        a in btree_set(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, ..=10_000),
+        b in btree_set(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, ..=10_000),
+        universe_end in BITMAP_MAX - 50_000..BITMAP_MAX + 50_000,
+    ) {
+        let mut treemap = RoaringTreemap::from_sorted_iter(a.iter().cloned()).unwrap();
+        let other = RoaringTreemap::from_sorted_iter(b.iter().cloned()).unwrap();
+        treemap.or_not(&other, universe_end);
+
+        let mut expected: BTreeSet<u64> = a;
+        expected.extend((BITMAP_MAX - 50_000..universe_end).filter(|v| !b.contains(v)));
+        prop_assert!(treemap.range(BITMAP_MAX - 50_000..).eq(expected.iter().cloned()));
+        prop_assert_eq!(treemap.range(..BITMAP_MAX - 50_000).len() as u64, BITMAP_MAX - 50_000);
+    }
+}
@@ -2,9 +2,11 @@ extern crate roaring;
 mod iter;
 use roaring::RoaringTreemap;
 
-use iter::outside_in;
+use iter::{outside_in, to_ranges};
 use proptest::arbitrary::any;
-use proptest::collection::btree_set;
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::prop_assert;
+use proptest::prelude::prop_assert_eq;
 use proptest::proptest;
 use std::iter::FromIterator;
 
@@ -104,7 +106,7 @@ fn from_iter() {
     // with u64 as well as &u64 elements.
     let vals = vec![1, 5, 1_000_000_000_000_000];
     let a = RoaringTreemap::from_iter(vals.iter());
-    let b = RoaringTreemap::from_iter(vals.into_iter());
+    let b = RoaringTreemap::from_iter(vals);
     assert_eq!(a, b);
 }
 
@@ -127,3 +129,158 @@ proptest! {
         assert!(outside_in(values).eq(outside_in(bitmap)));
     }
 }
+
+#[test]
+fn advance_to() {
+    let values = (1..3)
+        .chain(1_000_000..1_012_003)
+        .chain(2_000_001..2_000_003)
+        .chain(2_000_000_000_001..2_000_000_000_003)
+        .chain(u64::MAX - 2..=u64::MAX);
+    let bitmap = RoaringTreemap::from_iter(values);
+    let mut iter = bitmap.iter();
+
+    iter.advance_to(2);
+    assert_eq!(iter.next(), Some(2));
+    iter.advance_to(1_012_000);
+    assert_eq!(iter.next(), Some(1_012_000));
+    iter.advance_back_to(u64::MAX - 1);
+    assert_eq!(iter.next_back(), Some(u64::MAX - 1));
+    iter.advance_back_to(2_000_000_000_000);
+    assert_eq!(iter.next_back(), Some(2_000_002));
+    assert_eq!(iter.len(), 3);
+    iter.advance_to(u64::MAX);
+    assert_eq!(iter.len(), 0);
+    assert_eq!(iter.next(), None);
+}
+
+proptest! {
+    #[test]
+    fn advance_iter(
+        values in btree_set(0..4u64 << 32, ..=10_000),
+        ops in vec((0..5u8, 0..4u64 << 32), ..=50),
+    ) {
+        let bitmap = RoaringTreemap::from_sorted_iter(values.iter().cloned()).unwrap();
+        let values: Vec<u64> = values.into_iter().collect();
+
+        let mut iter = bitmap.iter();
+        let mut into_iter = bitmap.clone().into_iter();
+        let mut remaining = &values[..];
+        for (op, n) in ops {
+            match op {
+                0 => {
+                    prop_assert_eq!(iter.next(), remaining.first().cloned());
+                    prop_assert_eq!(into_iter.next(), remaining.first().cloned());
+                    remaining = remaining.get(1..).unwrap_or_default();
+                }
+                1 => {
+                    prop_assert_eq!(iter.next_back(), remaining.last().cloned());
+                    prop_assert_eq!(into_iter.next_back(), remaining.last().cloned());
+                    remaining = &remaining[..remaining.len().saturating_sub(1)];
+                }
+                2 => {
+                    iter.advance_to(n);
+                    let skipped = remaining.partition_point(|&v| v < n);
+                    into_iter.by_ref().take(skipped).for_each(drop);
+                    remaining = &remaining[remaining.partition_point(|&v| v < n)..];
+                }
+                3 => {
+                    iter.advance_back_to(n);
+                    let skipped = remaining.len() - remaining.partition_point(|&v| v <= n);
+                    into_iter.by_ref().rev().take(skipped).for_each(drop);
+                    remaining = &remaining[..remaining.partition_point(|&v| v <= n)];
+                }
+                _ => {
+                    let n = n as usize % 2_000;
+                    let expected = &remaining[..n.min(remaining.len())];
+                    remaining = &remaining[expected.len()..];
+                    let mut buf = vec![0; n];
+                    prop_assert_eq!(iter.next_many(&mut buf), expected.len());
+                    prop_assert_eq!(&buf[..expected.len()], expected);
+                    prop_assert_eq!(into_iter.next_many(&mut buf), expected.len());
+                    prop_assert_eq!(&buf[..expected.len()], expected);
+                }
+            }
+            prop_assert_eq!(iter.len(), remaining.len());
+            prop_assert_eq!(into_iter.len(), remaining.len());
+        }
+        prop_assert!(into_iter.eq(remaining.iter().cloned()));
+        prop_assert!(iter.eq(remaining.iter().cloned()));
+    }
+}
+
+#[test]
+fn range_bounds() {
+    let values = (1..3)
+        .chain(1_000_000..1_012_003)
+        .chain(2_000_001..2_000_003)
+        .chain(2_000_000_000_001..2_000_000_000_003)
+        .chain(u64::MAX - 2..=u64::MAX);
+    let bitmap = RoaringTreemap::from_iter(values.clone());
+
+    assert!(bitmap.range(2..1_000_005).eq((2..3).chain(1_000_000..1_000_005)));
+    assert!(bitmap.range(2_000_002..).rev().eq(values.clone().filter(|&v| v >= 2_000_002).rev()));
+    assert_eq!(bitmap.range(..=u64::MAX - 1).len(), values.count() - 1);
+    assert_eq!(bitmap.range(3..1_000_000).next(), None);
+}
+
+proptest! {
+    #[test]
+    fn range_iter(
+        values in btree_set(0..4u64 << 32, ..=10_000),
+        start in 0..4u64 << 32,
+        end in 0..4u64 << 32,
+    ) {
+        let bitmap = RoaringTreemap::from_sorted_iter(values.iter().cloned()).unwrap();
+        let expected = || values.range(start..=end.max(start)).cloned();
+
+        let range = bitmap.range(start..=end.max(start));
+        prop_assert_eq!(range.len(), expected().count());
+        prop_assert!(range.eq(expected()));
+        prop_assert!(bitmap.range(start..=end.max(start)).rev().eq(expected().rev()));
+    }
+}
+
+#[test]
+fn iter_ranges() {
+    let values = (1..3)
+        .chain(1_000_000..1_012_003)
+        .chain((1 << 32) - 3..(1 << 32) + 3)
+        .chain(2_000_000_000_001..2_000_000_000_003)
+        .chain(u64::MAX - 2..=u64::MAX);
+    let bitmap = RoaringTreemap::from_iter(values);
+    let expected = [
+        1..=2,
+        1_000_000..=1_012_002,
+        (1 << 32) - 3..=(1 << 32) + 2,
+        2_000_000_000_001..=2_000_000_000_002,
+        u64::MAX - 2..=u64::MAX,
+    ];
+
+    assert!(bitmap.iter_ranges().eq(expected.iter().cloned()));
+    assert!(bitmap.iter_ranges().rev().eq(expected.iter().cloned().rev()));
+}
+
+proptest! {
+    #[test]
+    fn iter_ranges_model(
+        values in btree_set((1u64 << 32) - 100..(1 << 32) + 100, ..=200),
+        backward in vec(any::<bool>(), ..=100),
+    ) {
+        let bitmap = RoaringTreemap::from_sorted_iter(values.iter().cloned()).unwrap();
+        let expected = to_ranges(values);
+
+        let mut iter = bitmap.iter_ranges();
+        let mut remaining = &expected[..];
+        for backward in backward {
+            if backward {
+                prop_assert_eq!(iter.next_back(), remaining.last().cloned());
+                remaining = &remaining[..remaining.len().saturating_sub(1)];
+            } else {
+                prop_assert_eq!(iter.next(), remaining.first().cloned());
+                remaining = remaining.get(1..).unwrap_or_default();
+            }
+        }
+        prop_assert!(iter.eq(remaining.iter().cloned()));
+    }
+}
@@ -0,0 +1,27 @@
+extern crate roaring;
+
+use roaring::RoaringTreemap;
+
+const BITMAP_MAX: u64 = u32::MAX as u64;
+
+#[test]
+fn memory_usage() {
+    assert_eq!(RoaringTreemap::new().memory_usage(), 0);
+
+    let mut treemap = RoaringTreemap::from_sorted_iter(0..4000).unwrap();
+    treemap.extend(BITMAP_MAX + 1..BITMAP_MAX + 4001);
+    let usage = treemap.memory_usage();
+    assert!(usage >= 2 * 4000 * 2);
+    assert_eq!(usage, treemap.statistics().n_heap_bytes as usize);
+
+    // Removing values keeps the capacity of the arrays
+    treemap.remove_range(10..BITMAP_MAX + 3991);
+    assert_eq!(treemap.memory_usage(), usage);
+
+    treemap.shrink_to_fit();
+    assert!(treemap.memory_usage() < usage);
+    assert!(treemap.iter().eq((0..10).chain(BITMAP_MAX + 3991..BITMAP_MAX + 4001)));
+
+    treemap.clear();
+    assert_eq!(treemap.memory_usage(), 0);
+}
@@ -0,0 +1,57 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::RoaringTreemap;
+
+const BITMAP_MAX: u64 = u32::MAX as u64;
+
+#[test]
+fn next_value() {
+    let treemap = RoaringTreemap::from_sorted_iter(BITMAP_MAX - 1000..BITMAP_MAX + 5000).unwrap();
+
+    assert_eq!(treemap.next_value(0), Some(BITMAP_MAX - 1000));
+    assert_eq!(treemap.next_value(BITMAP_MAX + 1), Some(BITMAP_MAX + 1));
+    assert_eq!(treemap.next_value(BITMAP_MAX + 5000), None);
+    assert_eq!(treemap.previous_value(u64::MAX), Some(BITMAP_MAX + 4999));
+    assert_eq!(treemap.previous_value(BITMAP_MAX - 1001), None);
+}
+
+#[test]
+fn next_absent() {
+    // The bitmap of the key 1 is full
+    let mut treemap = RoaringTreemap::new();
+    treemap.insert_range(BITMAP_MAX - 1000..BITMAP_MAX * 2 + 5000);
+
+    assert_eq!(treemap.next_absent(BITMAP_MAX - 1000), Some(BITMAP_MAX * 2 + 5000));
+    assert_eq!(treemap.previous_absent(BITMAP_MAX * 2 + 4999), Some(BITMAP_MAX - 1001));
+    assert_eq!(treemap.next_absent(0), Some(0));
+
+    treemap.insert_range(BITMAP_MAX * 2 + 5000..=BITMAP_MAX * 3 + 2);
+    assert_eq!(treemap.next_absent(BITMAP_MAX), Some(BITMAP_MAX * 3 + 3));
+
+    let mut treemap = RoaringTreemap::new();
+    treemap.insert_range(u64::MAX - BITMAP_MAX * 2..);
+    assert_eq!(treemap.next_absent(u64::MAX - BITMAP_MAX * 2), None);
+    assert_eq!(treemap.previous_absent(u64::MAX), Some(u64::MAX - BITMAP_MAX * 2 - 1));
+}
+
+proptest! {
+    #[test]
+    fn next_value_model(
+        values in btree_set(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, ..=10_000),
+        queries in vec(BITMAP_MAX - 60_000..BITMAP_MAX + 60_000, ..=100),
+    ) {
+        let treemap = RoaringTreemap::from_sorted_iter(values.iter().cloned()).unwrap();
+
+        for value in queries {
+            prop_assert_eq!(treemap.next_value(value), values.range(value..).next().cloned());
+            prop_assert_eq!(treemap.previous_value(value), values.range(..=value).next_back().cloned());
+            prop_assert_eq!(treemap.next_absent(value), (value..).find(|v| !values.contains(v)));
+            prop_assert_eq!(
+                treemap.previous_absent(value),
+                (0..=value).rev().find(|v| !values.contains(v))
+            );
+        }
+    }
+}
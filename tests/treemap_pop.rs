@@ -0,0 +1,94 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::{RoaringBitmap, RoaringTreemap};
+
+const BITMAP_MAX: u64 = u32::MAX as u64;
+
+#[test]
+fn pop() {
+    let mut treemap = RoaringTreemap::from_sorted_iter([1, BITMAP_MAX + 1, u64::MAX]).unwrap();
+
+    assert_eq!(treemap.pop_min(), Some(1));
+    assert_eq!(treemap.pop_max(), Some(u64::MAX));
+    assert_eq!(treemap.bitmaps().count(), 1);
+    assert_eq!(treemap.pop_max(), Some(BITMAP_MAX + 1));
+    assert_eq!(treemap.pop_min(), None);
+    assert_eq!(treemap.pop_max(), None);
+    assert!(treemap.is_empty());
+}
+
+#[test]
+fn pop_skips_empty_bitmaps() {
+    let bitmap: RoaringBitmap = [5].into_iter().collect();
+    let mut treemap = RoaringTreemap::from_bitmaps([
+        (0, RoaringBitmap::new()),
+        (1, bitmap),
+        (2, RoaringBitmap::new()),
+    ]);
+
+    assert_eq!(treemap.pop_max(), Some(BITMAP_MAX + 6));
+    assert!(treemap.is_empty());
+
+    let bitmap: RoaringBitmap = [5, 6].into_iter().collect();
+    let mut treemap = RoaringTreemap::from_bitmaps([
+        (0, RoaringBitmap::new()),
+        (1, bitmap),
+        (2, RoaringBitmap::new()),
+    ]);
+
+    assert_eq!(treemap.pop_min(), Some(BITMAP_MAX + 6));
+    assert_eq!(treemap.pop_max(), Some(BITMAP_MAX + 7));
+    assert_eq!(treemap.pop_min(), None);
+    assert_eq!(treemap.pop_max(), None);
+}
+
+#[test]
+fn remove_smallest_biggest() {
+    let mut treemap = RoaringTreemap::new();
+    treemap.insert_range(0..2000);
+    treemap.insert_range(BITMAP_MAX..BITMAP_MAX + 3000);
+    treemap.insert_range(u64::MAX - 1000..=u64::MAX);
+
+    assert_eq!(treemap.remove_smallest(0), 0);
+    assert_eq!(treemap.remove_smallest(2001), 2001);
+    assert_eq!(treemap.bitmaps().count(), 2);
+    assert!(treemap.iter().take(1000).eq(BITMAP_MAX + 1..BITMAP_MAX + 1001));
+
+    assert_eq!(treemap.remove_biggest(1500), 1500);
+    assert!(treemap.iter().eq(BITMAP_MAX + 1..BITMAP_MAX + 2501));
+
+    assert_eq!(treemap.remove_biggest(u64::MAX), 2500);
+    assert!(treemap.is_empty());
+    assert_eq!(treemap.remove_smallest(1), 0);
+}
+
+proptest! {
+    #[test]
+    fn remove_smallest_biggest_model(
+        values in btree_set(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, ..=10_000),
+        ranges in vec((BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, 0..20_000u64), ..=5),
+        smallest in 0..50_000u64,
+        biggest in 0..50_000u64,
+    ) {
+        let mut treemap = RoaringTreemap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            treemap.insert_range(start..start + len);
+        }
+        let mut model: Vec<u64> = treemap.iter().collect();
+
+        let removed = treemap.remove_smallest(smallest);
+        prop_assert_eq!(removed, smallest.min(model.len() as u64));
+        model.drain(..removed as usize);
+        prop_assert!(treemap.iter().eq(model.iter().cloned()));
+
+        let removed = treemap.remove_biggest(biggest);
+        prop_assert_eq!(removed, biggest.min(model.len() as u64));
+        model.truncate(model.len() - removed as usize);
+        prop_assert!(treemap.iter().eq(model.iter().cloned()));
+
+        prop_assert_eq!(treemap.pop_min(), model.first().cloned());
+        prop_assert_eq!(treemap.pop_max(), model.get(1..).and_then(|m| m.last()).cloned());
+    }
+}
@@ -0,0 +1,62 @@
+extern crate roaring;
+
+use proptest::collection::btree_set;
+use proptest::prelude::*;
+use roaring::RoaringTreemap;
+
+const BITMAP_MAX: u64 = u32::MAX as u64;
+
+#[test]
+fn similarity() {
+    let mut a = RoaringTreemap::from_sorted_iter(0..3000).unwrap();
+    a.insert_range(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000);
+    let b =
+        RoaringTreemap::from_sorted_iter((2000..4000).chain(BITMAP_MAX..BITMAP_MAX + 500)).unwrap();
+
+    let intersection = (1000 + 500) as f64;
+    let union = (103_000 + 1000) as f64;
+    assert_eq!(a.jaccard_index(&b), intersection / union);
+    assert_eq!(a.overlap_coefficient(&b), intersection / 2500.0);
+    assert_eq!(a.dice_coefficient(&b), 2.0 * intersection / 105_500.0);
+    assert_eq!(a.hamming_distance(&b), 103_000 + 2500 - 2 * 1500);
+
+    assert!(RoaringTreemap::new().jaccard_index(&RoaringTreemap::new()).is_nan());
+    assert_eq!(a.hamming_distance(&a), 0);
+}
+
+#[test]
+fn intersection_len_many() {
+    let mut a = RoaringTreemap::from_sorted_iter(0..3000).unwrap();
+    a.insert_range(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000);
+    let b =
+        RoaringTreemap::from_sorted_iter((2000..4000).chain(BITMAP_MAX..BITMAP_MAX + 500)).unwrap();
+    let c = RoaringTreemap::from_sorted_iter([u64::MAX]).unwrap();
+
+    assert_eq!(a.intersection_len_many(&[&b, &c, &a]), vec![1500, 0, a.len()]);
+    assert_eq!(RoaringTreemap::new().intersection_len_many(&[&a, &b]), vec![0, 0]);
+}
+
+proptest! {
+    #[test]
+    fn similarity_model(
+        a in btree_set(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, 1..=10_000),
+        b in btree_set(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, 1..=10_000),
+    ) {
+        let a = RoaringTreemap::from_sorted_iter(a).unwrap();
+        let b = RoaringTreemap::from_sorted_iter(b).unwrap();
+
+        let intersection = (&a & &b).len();
+        let union = (&a | &b).len();
+        prop_assert_eq!(a.jaccard_index(&b), intersection as f64 / union as f64);
+        prop_assert_eq!(
+            a.overlap_coefficient(&b),
+            intersection as f64 / a.len().min(b.len()) as f64
+        );
+        prop_assert_eq!(
+            a.dice_coefficient(&b),
+            2.0 * intersection as f64 / (a.len() + b.len()) as f64
+        );
+        prop_assert_eq!(a.hamming_distance(&b), (&a ^ &b).len());
+        prop_assert_eq!(a.intersection_len_many(&[&b, &a]), vec![intersection, a.len()]);
+    }
+}
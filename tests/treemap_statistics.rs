@@ -0,0 +1,63 @@
+extern crate roaring;
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use roaring::treemap::Statistics;
+use roaring::RoaringTreemap;
+
+const BITMAP_MAX: u64 = u32::MAX as u64;
+
+#[test]
+fn statistics_empty() {
+    let stats = RoaringTreemap::new().statistics();
+    assert_eq!(stats, Statistics::default());
+}
+
+#[test]
+fn statistics() {
+    let mut treemap = RoaringTreemap::from_sorted_iter((0..3000).step_by(3)).unwrap();
+    treemap.extend(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000);
+    treemap.insert(u64::MAX);
+
+    let stats = treemap.statistics();
+    assert_eq!(stats.n_bitmaps, 3);
+    assert_eq!(stats.n_containers, 4);
+    assert_eq!(stats.n_array_containers, 2);
+    assert_eq!(stats.n_bitset_containers, 2);
+    assert_eq!(stats.n_values_array_containers, 1001);
+    assert_eq!(stats.n_values_bitset_containers, 100_000);
+    assert_eq!(stats.n_bytes_bitset_containers, 2 * 8192);
+    assert_eq!(stats.cardinality, treemap.len());
+    assert_eq!(stats.min_value, Some(0));
+    assert_eq!(stats.max_value, Some(u64::MAX));
+    assert!(stats.n_heap_bytes >= 2 * 8192 + 2 * 1001);
+}
+
+proptest! {
+    #[test]
+    fn statistics_model(
+        values in btree_set(BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, ..=10_000),
+        ranges in vec((BITMAP_MAX - 50_000..BITMAP_MAX + 50_000, 0..20_000u64), ..=5),
+    ) {
+        let mut treemap = RoaringTreemap::from_sorted_iter(values).unwrap();
+        for (start, len) in ranges {
+            treemap.insert_range(start..start + len);
+        }
+
+        let stats = treemap.statistics();
+        let inner: Vec<_> = treemap.bitmaps().map(|(_, bitmap)| bitmap.statistics()).collect();
+        prop_assert_eq!(stats.n_bitmaps, inner.len() as u64);
+        prop_assert_eq!(stats.n_containers, inner.iter().map(|s| s.n_containers).sum::<u64>());
+        prop_assert_eq!(
+            stats.n_bytes_array_containers + stats.n_bytes_bitset_containers,
+            inner
+                .iter()
+                .map(|s| s.n_bytes_array_containers + s.n_bytes_bitset_containers)
+                .sum::<u64>()
+        );
+        prop_assert!(stats.n_heap_bytes >= inner.iter().map(|s| s.n_heap_bytes).sum::<u64>());
+        prop_assert_eq!(stats.cardinality, treemap.len());
+        prop_assert_eq!(stats.min_value, treemap.min());
+        prop_assert_eq!(stats.max_value, treemap.max());
+    }
+}